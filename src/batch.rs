@@ -0,0 +1,279 @@
+//! QR code generation for many accounts at once
+
+use crate::{EasyTotp, EasyTotpError, MAX_IMAGE_SIZE, render_qr_image};
+
+use ab_glyph::{Font, FontRef};
+use image::codecs::png::PngEncoder;
+use image::{GrayImage, Luma, imageops};
+use imageproc::drawing::{draw_text_mut, text_size};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::error::Error;
+
+/// The caption typeface; see `assets/LICENSE-DejaVu`
+const CAPTION_FONT: &[u8] = include_bytes!("../assets/DejaVuSansMono.ttf");
+/// Height of caption text, in pixels
+const CAPTION_SCALE: f32 = 20.0;
+/// Space reserved below each QR code for its caption, in pixels
+const CAPTION_HEIGHT: u32 = 32;
+
+/// Creates a PNG with a QR code for every account, e.g. for bulk enrollment
+///
+/// Returns one result per account, in the same order, so a single failure doesn't abort the batch.
+/// With the `rayon` feature, the QR codes are generated in parallel.
+///
+/// BEWARE: PNG images contain secrets!!
+///
+/// ```rust
+/// use easy_totp::{EasyTotp, batch_qr_pngs};
+///
+/// let accounts = vec![
+///     EasyTotp::new(Some(String::from("McCormick")), String::from("alice@test-email.com")).unwrap(),
+///     EasyTotp::new(Some(String::from("McCormick")), String::from("bob@test-email.com")).unwrap(),
+/// ];
+///
+/// for png in batch_qr_pngs(&accounts) {
+///     let png_data = png.unwrap();
+/// }
+/// ```
+#[must_use]
+pub fn batch_qr_pngs(accounts: &[EasyTotp]) -> Vec<Result<Vec<u8>, Box<dyn Error>>> {
+    #[cfg(feature = "rayon")]
+    let results: Vec<_> = accounts.par_iter().map(EasyTotp::qr_png_bytes).collect();
+    #[cfg(not(feature = "rayon"))]
+    let results: Vec<_> = accounts.iter().map(EasyTotp::qr_png_bytes).collect();
+
+    results
+        .into_iter()
+        .map(|result| result.map_err(Into::into))
+        .collect()
+}
+
+/// Creates a single PNG with a grid of QR codes, `columns` wide, each captioned with its account name, e.g. for printing enrollment cards
+///
+/// Every cell is as large as the largest QR code, since longer URIs need more modules. Captions too wide for their cell are shortened with `…`.
+/// With the `rayon` feature, the QR codes are generated in parallel.
+///
+/// BEWARE: PNG image contains secrets!!
+///
+/// ```rust
+/// use easy_totp::{EasyTotp, contact_sheet};
+///
+/// let accounts = vec![
+///     EasyTotp::new(Some(String::from("McCormick")), String::from("alice@test-email.com")).unwrap(),
+///     EasyTotp::new(Some(String::from("McCormick")), String::from("bob@test-email.com")).unwrap(),
+///     EasyTotp::new(Some(String::from("McCormick")), String::from("carol@test-email.com")).unwrap(),
+/// ];
+///
+/// let png_data = contact_sheet(&accounts, 2).unwrap();
+/// ```
+///
+/// ## Errors
+/// This function will return an error if `accounts` is empty, `columns` is 0, the sheet would be too large, or generating any QR code fails.
+pub fn contact_sheet(accounts: &[EasyTotp], columns: usize) -> Result<Vec<u8>, Box<dyn Error>> {
+    if accounts.is_empty() {
+        return Err(EasyTotpError::InvalidSetting(String::from(
+            "a contact sheet needs at least one account",
+        ))
+        .into());
+    }
+    if columns == 0 {
+        return Err(EasyTotpError::InvalidSetting(String::from(
+            "a contact sheet needs at least one column",
+        ))
+        .into());
+    }
+
+    #[cfg(feature = "rayon")]
+    let qr_codes: Vec<_> = accounts
+        .par_iter()
+        .map(qr_image)
+        .collect::<Result<_, _>>()?;
+    #[cfg(not(feature = "rayon"))]
+    let qr_codes: Vec<_> = accounts.iter().map(qr_image).collect::<Result<_, _>>()?;
+
+    let qr_width = qr_codes.iter().map(GrayImage::width).max().unwrap_or(0);
+    let qr_height = qr_codes.iter().map(GrayImage::height).max().unwrap_or(0);
+    let cell_height = qr_height + CAPTION_HEIGHT;
+
+    let columns = columns.min(accounts.len());
+    let rows = accounts.len().div_ceil(columns);
+    let sheet_size = |count: usize, cell: u32| {
+        u32::try_from(count)
+            .ok()
+            .and_then(|count| count.checked_mul(cell))
+            .filter(|&size| size <= MAX_IMAGE_SIZE)
+    };
+    let (Some(width), Some(height)) =
+        (sheet_size(columns, qr_width), sheet_size(rows, cell_height))
+    else {
+        return Err(EasyTotpError::InvalidSetting(format!(
+            "a contact sheet of {columns} columns and {rows} rows is too large"
+        ))
+        .into());
+    };
+
+    let font = FontRef::try_from_slice(CAPTION_FONT)?;
+    let mut sheet = GrayImage::from_pixel(width, height, Luma([255]));
+
+    // Both fit in `MAX_IMAGE_SIZE`, so every position below does too
+    for ((column, row), (account, qr)) in (0..columns)
+        .cycle()
+        .zip((0..).flat_map(|row| std::iter::repeat_n(row, columns)))
+        .zip(accounts.iter().zip(&qr_codes))
+    {
+        let left = u32::try_from(column).unwrap_or(0) * qr_width;
+        let top = u32::try_from(row).unwrap_or(0) * cell_height;
+
+        imageops::overlay(
+            &mut sheet,
+            qr,
+            i64::from(left + (qr_width - qr.width()) / 2),
+            i64::from(top + (qr_height - qr.height()) / 2),
+        );
+
+        let caption = fit_caption(&font, &account.account_name, qr_width);
+        let (caption_width, caption_height) = text_size(CAPTION_SCALE, &font, &caption);
+        draw_text_mut(
+            &mut sheet,
+            Luma([0]),
+            (left + qr_width.saturating_sub(caption_width) / 2).cast_signed(),
+            (top + qr_height + CAPTION_HEIGHT.saturating_sub(caption_height) / 2).cast_signed(),
+            CAPTION_SCALE,
+            &font,
+            &caption,
+        );
+    }
+
+    let mut buffer = Vec::new();
+    sheet.write_with_encoder(PngEncoder::new(&mut buffer))?;
+
+    Ok(buffer)
+}
+
+/// Renders the QR code of one account at the same scale as `EasyTotp::create_qr_png`
+fn qr_image(account: &EasyTotp) -> Result<GrayImage, EasyTotpError> {
+    render_qr_image(&account.to_otpauth_uri()?, account.qr_options(), 8)
+}
+
+/// Shortens `text` with a trailing `…` until it is at most `max_width` pixels wide
+fn fit_caption(font: &impl Font, text: &str, max_width: u32) -> String {
+    let fits = |caption: &str| text_size(CAPTION_SCALE, font, caption).0 <= max_width;
+    if fits(text) {
+        return String::from(text);
+    }
+
+    let mut chars: Vec<char> = text.chars().collect();
+    while chars.pop().is_some() {
+        let caption: String = chars.iter().chain(['…'].iter()).collect();
+        if fits(&caption) {
+            return caption;
+        }
+    }
+
+    String::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zeroize::Zeroizing;
+
+    #[test]
+    fn test_batch_qr_pngs() {
+        let account = |name: &str| {
+            EasyTotp::from_base32_secret(
+                "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ",
+                Some(String::from("McCormick")),
+                String::from(name),
+            )
+            .unwrap()
+        };
+        // Too short a secret, so generating its QR code fails
+        let broken = EasyTotp {
+            raw_secret: Zeroizing::new(b"too short".to_vec()),
+            ..account("broken@test-email.com")
+        };
+
+        let accounts = [
+            account("alice@test-email.com"),
+            broken,
+            account("bob@test-email.com"),
+        ];
+        let results = batch_qr_pngs(&accounts);
+
+        assert_eq!(results.len(), 3);
+        assert!(results[1].is_err());
+        for index in [0, 2] {
+            let png = results[index].as_ref().unwrap();
+            assert_eq!(png, &accounts[index].create_qr_png().unwrap());
+        }
+
+        assert!(batch_qr_pngs(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_contact_sheet() {
+        let account = |name: &str| {
+            EasyTotp::from_base32_secret(
+                "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ",
+                Some(String::from("McCormick")),
+                String::from(name),
+            )
+            .unwrap()
+        };
+        let accounts = [
+            account("alice@test-email.com"),
+            account("bob@test-email.com"),
+            account("carol@test-email.com"),
+        ];
+
+        let png = contact_sheet(&accounts, 2).unwrap();
+        let sheet = image::load_from_memory(&png).unwrap().to_luma8();
+        let qr = qr_image(&accounts[0]).unwrap();
+        let (qr_width, qr_height) = (qr.width(), qr.height());
+        assert_eq!(sheet.width(), qr_width * 2);
+        assert_eq!(sheet.height(), (qr_height + CAPTION_HEIGHT) * 2);
+
+        // Every cell decodes back to its own account
+        for (index, account) in accounts.iter().enumerate() {
+            let index = u32::try_from(index).unwrap();
+            let cell = imageops::crop_imm(
+                &sheet,
+                index % 2 * qr_width,
+                index / 2 * (qr_height + CAPTION_HEIGHT),
+                qr_width,
+                qr_height,
+            )
+            .to_image();
+            let mut decoder = rqrr::PreparedImage::prepare(cell);
+            let (_, content) = decoder.detect_grids()[0].decode().unwrap();
+            assert_eq!(content, account.to_otpauth_uri().unwrap());
+        }
+
+        // The caption below the first QR code is drawn in dark pixels
+        let caption = imageops::crop_imm(&sheet, 0, qr_height, qr_width, CAPTION_HEIGHT);
+        assert!(caption.to_image().pixels().any(|pixel| pixel[0] < 128));
+
+        // More columns than accounts collapses to a single row
+        let png = contact_sheet(&accounts, 10).unwrap();
+        let sheet = image::load_from_memory(&png).unwrap();
+        assert_eq!(sheet.width(), qr_width * 3);
+
+        assert!(contact_sheet(&[], 2).is_err());
+        assert!(contact_sheet(&accounts, 0).is_err());
+    }
+
+    #[test]
+    fn test_fit_caption() {
+        let font = FontRef::try_from_slice(CAPTION_FONT).unwrap();
+
+        assert_eq!(fit_caption(&font, "bob", 1_000), "bob");
+
+        let caption = fit_caption(&font, "a-very-long-account-name@test-email.com", 200);
+        assert!(caption.ends_with('…'));
+        assert!(text_size(CAPTION_SCALE, &font, &caption).0 <= 200);
+
+        assert_eq!(fit_caption(&font, "bob", 1), "");
+    }
+}
@@ -0,0 +1,191 @@
+//! Step-by-step construction of `EasyTotp` instances
+
+use crate::{
+    DEFAULT_SECRET_LEN, EasyTotp, EasyTotpError, STEAM_DIGITS, STEAM_PERIOD, random_secret,
+    redacted_secret, validate_names_not_blank,
+};
+
+use std::fmt;
+use totp_rs::Algorithm;
+use zeroize::Zeroizing;
+
+/// `EasyTotpBuilder` configures an `EasyTotp` one setting at a time
+///
+/// Every setting is optional and defaults to the same value as `EasyTotp::new`. All of them are validated by `build`.
+///
+/// ```rust
+/// use easy_totp::{Algorithm, EasyTotp};
+///
+/// let et = EasyTotp::builder()
+///     .issuer("McCormick")
+///     .account_name("test@test-email.com")
+///     .algorithm(Algorithm::SHA1)
+///     .digits(8)
+///     .period(60)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Default)]
+pub struct EasyTotpBuilder {
+    secret: Option<Zeroizing<Vec<u8>>>,
+    issuer: Option<String>,
+    account_name: String,
+    algorithm: Option<Algorithm>,
+    digits: Option<usize>,
+    period: Option<u64>,
+    skew: Option<u8>,
+    min_secret_len: Option<usize>,
+    require_issuer: bool,
+    icon_url: Option<String>,
+}
+
+impl fmt::Debug for EasyTotpBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EasyTotpBuilder")
+            .field(
+                "secret",
+                &self.secret.as_deref().map(|secret| redacted_secret(secret)),
+            )
+            .field("issuer", &self.issuer)
+            .field("account_name", &self.account_name)
+            .field("algorithm", &self.algorithm)
+            .field("digits", &self.digits)
+            .field("period", &self.period)
+            .field("skew", &self.skew)
+            .field("min_secret_len", &self.min_secret_len)
+            .field("require_issuer", &self.require_issuer)
+            .field("icon_url", &self.icon_url)
+            .finish()
+    }
+}
+
+impl EasyTotpBuilder {
+    /// Sets the issuer (the name of your service) shown in authenticator apps
+    #[must_use]
+    pub fn issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Sets the account name (e.g. the user's email address) shown in authenticator apps
+    #[must_use]
+    pub fn account_name(mut self, account_name: impl Into<String>) -> Self {
+        self.account_name = account_name.into();
+        self
+    }
+
+    /// Sets the hash algorithm; see `EasyTotp::with_algorithm`
+    #[must_use]
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = Some(algorithm);
+        self
+    }
+
+    /// Sets the number of digits in each token; see `EasyTotp::with_digits`
+    #[must_use]
+    pub fn digits(mut self, digits: usize) -> Self {
+        self.digits = Some(digits);
+        self
+    }
+
+    /// Sets the time step in seconds; see `EasyTotp::with_period`
+    #[must_use]
+    pub fn period(mut self, seconds: u64) -> Self {
+        self.period = Some(seconds);
+        self
+    }
+
+    /// Sets the verification skew; see `EasyTotp::with_skew`
+    #[must_use]
+    pub fn skew(mut self, steps: u8) -> Self {
+        self.skew = Some(steps);
+        self
+    }
+
+    /// Sets the minimum secret key length in bytes; see `EasyTotp::with_min_secret_length`
+    #[must_use]
+    pub fn min_secret_length(mut self, bytes: usize) -> Self {
+        self.min_secret_len = Some(bytes);
+        self
+    }
+
+    /// Sets the icon shown by supporting authenticator apps; see `EasyTotp::with_icon_url`
+    #[must_use]
+    pub fn icon_url(mut self, url: impl Into<String>) -> Self {
+        self.icon_url = Some(url.into());
+        self
+    }
+
+    /// Makes `build` fail unless a non-empty issuer was set
+    ///
+    /// Without an issuer, authenticator apps list the account by its account name alone, e.g. just an email address,
+    /// which leaves users guessing which service the codes are for.
+    #[must_use]
+    pub fn require_issuer(mut self) -> Self {
+        self.require_issuer = true;
+        self
+    }
+
+    /// Uses an existing raw secret key instead of generating a random one
+    ///
+    /// BEWARE: handle secrets with caution!!
+    #[must_use]
+    pub fn secret_bytes(mut self, secret: &[u8]) -> Self {
+        self.secret = Some(Zeroizing::new(secret.to_vec()));
+        self
+    }
+
+    /// Validates every setting and creates the `EasyTotp` instance, generating a random secret key if none was supplied
+    ///
+    /// ## Errors
+    /// This function will return an error if a setting is out of range, the secret key is shorter than 128 bits (or `min_secret_length`),
+    /// the account name is missing or blank, the issuer is blank or missing with `require_issuer`, the issuer or account name contain a `:`,
+    /// the icon URL is invalid, or the random number generator fails.
+    pub fn build(self) -> Result<EasyTotp, EasyTotpError> {
+        if self.require_issuer
+            && self
+                .issuer
+                .as_deref()
+                .is_none_or(|issuer| issuer.trim().is_empty())
+        {
+            return Err(EasyTotpError::InvalidSetting(String::from(
+                "issuer is required, so that authenticator apps show which service the account belongs to",
+            )));
+        }
+        validate_names_not_blank(self.issuer.as_deref(), &self.account_name)?;
+
+        let raw_secret = match self.secret {
+            Some(secret) => secret,
+            None => random_secret(DEFAULT_SECRET_LEN)?,
+        };
+
+        let defaults = EasyTotp::default();
+        let algorithm = self.algorithm.unwrap_or(defaults.algorithm);
+        // Steam Guard has its own fixed format
+        let (digits, period) = if algorithm == Algorithm::Steam {
+            (STEAM_DIGITS, STEAM_PERIOD)
+        } else {
+            (defaults.digits, defaults.period)
+        };
+
+        let et = EasyTotp {
+            raw_secret,
+            issuer: self.issuer,
+            account_name: self.account_name,
+            algorithm,
+            skew: self.skew.unwrap_or(defaults.skew),
+            min_secret_len: self.min_secret_len.unwrap_or(defaults.min_secret_len),
+            ..defaults
+        }
+        .with_token_format(self.digits.unwrap_or(digits), self.period.unwrap_or(period))?;
+        let et = match self.icon_url {
+            Some(icon_url) => et.with_icon_url(icon_url)?,
+            None => et,
+        };
+
+        // Building the `TOTP` instance checks the secret length and the issuer and account name
+        et.totp()?;
+
+        Ok(et)
+    }
+}
@@ -0,0 +1,355 @@
+//! A `no_std` core for generating and verifying TOTP tokens, e.g. on microcontrollers
+//!
+//! Everything here only needs `alloc`, and is available with `default-features = false`. There is no QR code, terminal,
+//! or file support, and no clock: pass the Unix time in, or implement `Clock` for your hardware's real-time clock.
+
+use crate::{Clock, MIN_SECRET_LEN, redacted_secret};
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use constant_time_eq::constant_time_eq;
+use core::fmt;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+use zeroize::Zeroizing;
+
+/// `HashAlgorithm` is the HMAC hash function of a `TotpCore`
+///
+/// SHA1 is what nearly every authenticator app expects.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum HashAlgorithm {
+    /// HMAC-SHA1, the RFC 6238 default
+    #[default]
+    SHA1,
+    /// HMAC-SHA256
+    SHA256,
+    /// HMAC-SHA512
+    SHA512,
+}
+
+/// `TotpCoreError` describes an invalid `TotpCore` setting
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum TotpCoreError {
+    /// The secret key is shorter than 128 bits
+    SecretTooShort(usize),
+    /// The number of digits is outside `6..=8`
+    InvalidDigits(usize),
+    /// The period is zero
+    InvalidPeriod,
+}
+
+impl fmt::Display for TotpCoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TotpCoreError::SecretTooShort(len) => write!(
+                f,
+                "secret key must be at least {MIN_SECRET_LEN} bytes, not {len}"
+            ),
+            TotpCoreError::InvalidDigits(digits) => {
+                write!(f, "digits must be between 6 and 8, not {digits}")
+            }
+            TotpCoreError::InvalidPeriod => write!(f, "period must be greater than zero"),
+        }
+    }
+}
+
+impl core::error::Error for TotpCoreError {}
+
+/// `TotpCore` generates and verifies RFC 6238 tokens without `std`
+///
+/// Tokens match those of an `EasyTotp` with the same secret key and settings. The defaults are the same too:
+/// SHA1, 6 digits, a 30 second period, and a skew of 1 step.
+///
+/// BEWARE: handle secrets with caution!! The secret key is zeroized on drop and redacted from `Debug` output.
+///
+/// ```rust
+/// use easy_totp::embedded::TotpCore;
+///
+/// let core = TotpCore::new(b"12345678901234567890").unwrap();
+///
+/// let token = core.generate_token_at(59);
+/// assert_eq!(token, "287082");
+/// assert!(core.verify_token_at(&token, 59));
+/// ```
+#[derive(Clone, Eq, PartialEq)]
+pub struct TotpCore {
+    secret: Zeroizing<Vec<u8>>,
+    algorithm: HashAlgorithm,
+    digits: usize,
+    period: u64,
+    skew: u8,
+}
+
+impl fmt::Debug for TotpCore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TotpCore")
+            .field("secret", &redacted_secret(&self.secret))
+            .field("algorithm", &self.algorithm)
+            .field("digits", &self.digits)
+            .field("period", &self.period)
+            .field("skew", &self.skew)
+            .finish()
+    }
+}
+
+impl TotpCore {
+    /// Creates a `TotpCore` from a raw secret key, with the default settings
+    ///
+    /// BEWARE: handle secrets with caution!!
+    ///
+    /// ## Errors
+    /// This function will return an error if the secret key is shorter than 128 bits.
+    pub fn new(secret: &[u8]) -> Result<Self, TotpCoreError> {
+        if secret.len() < MIN_SECRET_LEN {
+            return Err(TotpCoreError::SecretTooShort(secret.len()));
+        }
+
+        Ok(TotpCore {
+            secret: Zeroizing::new(secret.to_vec()),
+            algorithm: HashAlgorithm::default(),
+            digits: 6,
+            period: 30,
+            skew: 1,
+        })
+    }
+
+    /// Sets the hash algorithm
+    #[must_use]
+    pub fn with_algorithm(mut self, algorithm: HashAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Sets the number of digits in each token, from 6 to 8
+    ///
+    /// ## Errors
+    /// This function will return an error if `digits` is outside `6..=8`.
+    pub fn with_digits(mut self, digits: usize) -> Result<Self, TotpCoreError> {
+        if !(6..=8).contains(&digits) {
+            return Err(TotpCoreError::InvalidDigits(digits));
+        }
+
+        self.digits = digits;
+        Ok(self)
+    }
+
+    /// Sets the time step in seconds
+    ///
+    /// ## Errors
+    /// This function will return an error if `seconds` is zero.
+    pub fn with_period(mut self, seconds: u64) -> Result<Self, TotpCoreError> {
+        if seconds == 0 {
+            return Err(TotpCoreError::InvalidPeriod);
+        }
+
+        self.period = seconds;
+        Ok(self)
+    }
+
+    /// Sets how many time steps before and after the current one `verify_token_at` accepts
+    #[must_use]
+    pub fn with_skew(mut self, steps: u8) -> Self {
+        self.skew = steps;
+        self
+    }
+
+    /// Generates the token for the current time of `clock`
+    #[must_use]
+    pub fn generate_token(&self, clock: &impl Clock) -> String {
+        self.generate_token_at(clock.now_unix())
+    }
+
+    /// Generates the token for the given Unix timestamp, in seconds
+    #[must_use]
+    pub fn generate_token_at(&self, unix_time: u64) -> String {
+        self.generate_step(unix_time / self.period)
+    }
+
+    /// Verifies a user-submitted token against the current time of `clock`
+    #[must_use]
+    pub fn verify_token(&self, token: &str, clock: &impl Clock) -> bool {
+        self.verify_token_at(token, clock.now_unix())
+    }
+
+    /// Verifies a user-submitted token against the given Unix timestamp, in seconds, allowing for the skew
+    ///
+    /// Every step of the window is compared in constant time, without exiting early.
+    #[must_use]
+    pub fn verify_token_at(&self, token: &str, unix_time: u64) -> bool {
+        let step = unix_time / self.period;
+        // Clip the window at the Unix epoch instead of underflowing
+        let first_step = step.saturating_sub(u64::from(self.skew));
+        let last_step = step.saturating_add(u64::from(self.skew));
+
+        let mut matched = false;
+        for s in first_step..=last_step {
+            matched |= constant_time_eq(self.generate_step(s).as_bytes(), token.as_bytes());
+        }
+        matched
+    }
+
+    /// Generates the token for an RFC 6238 time step counter, as in RFC 4226
+    fn generate_step(&self, step: u64) -> String {
+        let counter = step.to_be_bytes();
+        let digest = match self.algorithm {
+            HashAlgorithm::SHA1 => hmac_digest::<Hmac<Sha1>>(&self.secret, &counter),
+            HashAlgorithm::SHA256 => hmac_digest::<Hmac<Sha256>>(&self.secret, &counter),
+            HashAlgorithm::SHA512 => hmac_digest::<Hmac<Sha512>>(&self.secret, &counter),
+        };
+
+        // Dynamic truncation: the low nibble of the last byte picks 4 bytes, minus their top bit
+        let offset = usize::from(digest[digest.len() - 1] & 0x0f);
+        let code = u32::from_be_bytes([
+            digest[offset] & 0x7f,
+            digest[offset + 1],
+            digest[offset + 2],
+            digest[offset + 3],
+        ]);
+
+        // `digits` is at most 8, so this can't truncate
+        #[allow(clippy::cast_possible_truncation)]
+        let modulus = 10_u32.pow(self.digits as u32);
+        format!("{:0width$}", code % modulus, width = self.digits)
+    }
+}
+
+/// Computes the HMAC of `message`
+fn hmac_digest<M: Mac + hmac::digest::KeyInit>(key: &[u8], message: &[u8]) -> Vec<u8> {
+    // HMAC accepts keys of any length
+    let mut mac =
+        <M as hmac::digest::KeyInit>::new_from_slice(key).expect("HMAC takes any key length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RFC_SECRET_SHA1: &[u8] = b"12345678901234567890";
+    const RFC_SECRET_SHA256: &[u8] = b"12345678901234567890123456789012";
+    const RFC_SECRET_SHA512: &[u8] =
+        b"1234567890123456789012345678901234567890123456789012345678901234";
+
+    #[test]
+    fn test_rfc6238_test_vectors() {
+        // RFC 6238 Appendix B
+        let vectors: [(u64, &str, &str, &str); 6] = [
+            (59, "94287082", "46119246", "90693936"),
+            (1_111_111_109, "07081804", "68084774", "25091201"),
+            (1_111_111_111, "14050471", "67062674", "99943326"),
+            (1_234_567_890, "89005924", "91819424", "93441116"),
+            (2_000_000_000, "69279037", "90698825", "38618901"),
+            (20_000_000_000, "65353130", "77737706", "47863826"),
+        ];
+
+        for (secret, algorithm, column) in [
+            (RFC_SECRET_SHA1, HashAlgorithm::SHA1, 0),
+            (RFC_SECRET_SHA256, HashAlgorithm::SHA256, 1),
+            (RFC_SECRET_SHA512, HashAlgorithm::SHA512, 2),
+        ] {
+            let core = TotpCore::new(secret)
+                .unwrap()
+                .with_algorithm(algorithm)
+                .with_digits(8)
+                .unwrap();
+            for (time, sha1, sha256, sha512) in vectors {
+                let expected = [sha1, sha256, sha512][column];
+                assert_eq!(core.generate_token_at(time), expected);
+                assert!(core.verify_token_at(expected, time));
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_token_at() {
+        let core = TotpCore::new(RFC_SECRET_SHA1).unwrap();
+        let token = core.generate_token_at(1_000_000);
+
+        assert!(core.verify_token_at(&token, 1_000_000));
+        assert!(core.verify_token_at(&token, 1_000_000 + 30));
+        assert!(core.verify_token_at(&token, 1_000_000 - 30));
+        assert!(!core.verify_token_at(&token, 1_000_000 + 60));
+        assert!(!core.verify_token_at("", 1_000_000));
+        assert!(!core.verify_token_at(&token[..5], 1_000_000));
+
+        // The window is clipped at the epoch
+        let core = core.with_skew(3);
+        assert!(core.verify_token_at(&core.generate_token_at(0), 10));
+
+        let clock = || 1_000_000;
+        assert_eq!(core.generate_token(&clock), token);
+        assert!(core.verify_token(&token, &clock));
+    }
+
+    #[test]
+    fn test_invalid_settings() {
+        assert_eq!(
+            TotpCore::new(&[0; 15]).unwrap_err(),
+            TotpCoreError::SecretTooShort(15)
+        );
+        let core = TotpCore::new(RFC_SECRET_SHA1).unwrap();
+        assert_eq!(
+            core.clone().with_digits(5).unwrap_err(),
+            TotpCoreError::InvalidDigits(5)
+        );
+        assert_eq!(
+            core.clone().with_digits(9).unwrap_err(),
+            TotpCoreError::InvalidDigits(9)
+        );
+        assert_eq!(
+            core.with_period(0).unwrap_err(),
+            TotpCoreError::InvalidPeriod
+        );
+    }
+
+    #[test]
+    fn test_debug_redacts_secret() {
+        let core = TotpCore::new(RFC_SECRET_SHA1).unwrap();
+        let debug = format!("{core:?}");
+        assert!(debug.contains("<redacted 20 bytes>"));
+        assert!(!debug.contains("1234567890"));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_matches_easy_totp() {
+        use crate::{Algorithm, EasyTotp};
+
+        for (algorithm, core_algorithm) in [
+            (Algorithm::SHA1, HashAlgorithm::SHA1),
+            (Algorithm::SHA256, HashAlgorithm::SHA256),
+            (Algorithm::SHA512, HashAlgorithm::SHA512),
+        ] {
+            let et = EasyTotp::builder()
+                .issuer("McCormick")
+                .account_name("test@test-email.com")
+                .secret_bytes(RFC_SECRET_SHA512)
+                .algorithm(algorithm)
+                .digits(7)
+                .period(45)
+                .build()
+                .unwrap();
+            let core = TotpCore::new(RFC_SECRET_SHA512)
+                .unwrap()
+                .with_algorithm(core_algorithm)
+                .with_digits(7)
+                .unwrap()
+                .with_period(45)
+                .unwrap();
+
+            for time in [0, 44, 45, 1_700_000_000, u64::from(u32::MAX)] {
+                let token = et.generate_token_at(time).unwrap();
+                assert_eq!(core.generate_token_at(time), token);
+                assert_eq!(
+                    core.verify_token_at(&token, time + 45),
+                    et.verify_token_at(&token, time + 45).unwrap()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,520 @@
+//! Password-based encryption of `EasyTotp` instances, for storing them at rest
+//!
+//! The encrypted format is:
+//!
+//! ```text
+//! "EZTOTP" | version (1 byte) | Argon2id salt (16 bytes) | nonce (12 bytes) | ciphertext + Poly1305 tag
+//! ```
+//!
+//! The key is derived from the password with Argon2id (default parameters), and the settings are encrypted with
+//! ChaCha20-Poly1305, with the header as associated data. The plaintext is a simple length-prefixed layout:
+//!
+//! ```text
+//! secret len (u16) | secret | has issuer (u8) | [issuer len (u16) | issuer] | account name len (u16) | account name |
+//! algorithm (u8) | digits (u8) | period (u64) | skew (u8) | QR ECC level (u8) | quiet zone (u8) | min secret len (u16) |
+//! has icon URL (u8) | [icon URL len (u16) | icon URL]
+//! ```
+//!
+//! All integers are big-endian.
+
+use crate::{EasyTotp, EasyTotpError, QREccLevel, migration};
+
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use rand::{TryRngCore, rngs::OsRng};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::Path;
+use totp_rs::Algorithm;
+use zeroize::Zeroizing;
+
+/// Identifies encrypted `EasyTotp` data
+const MAGIC: &[u8] = b"EZTOTP";
+/// Version of the encrypted format
+const VERSION: u8 = 1;
+/// Length of the Argon2id salt
+const SALT_LEN: usize = 16;
+/// Length of the ChaCha20-Poly1305 key
+const KEY_LEN: usize = 32;
+/// Length of the ChaCha20-Poly1305 nonce
+const NONCE_LEN: usize = 12;
+/// Length of the header, which is also the associated data
+const HEADER_LEN: usize = MAGIC.len() + 1 + SALT_LEN + NONCE_LEN;
+
+impl EasyTotp {
+    /// Encrypts every setting, including the secret key, with a password
+    ///
+    /// Unlike the `Serialize` implementation of the `plaintext-serde` feature, which writes the secret in plaintext, this is safe to store in a database or file.
+    /// Decrypt it again with `EasyTotp::from_encrypted`.
+    ///
+    /// The key is derived with Argon2id, so this deliberately takes a noticeable amount of time and memory.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let encrypted = et.to_encrypted("correct horse battery staple").unwrap();
+    /// let decrypted = EasyTotp::from_encrypted(&encrypted, "correct horse battery staple").unwrap();
+    /// assert_eq!(decrypted, et);
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the secret, issuer, or account name are longer than 65535 bytes,
+    /// if the random number generator fails, or if the key derivation or encryption fails.
+    pub fn to_encrypted(&self, password: &str) -> Result<Vec<u8>, EasyTotpError> {
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        if let Err(e) = OsRng
+            .try_fill_bytes(&mut salt)
+            .and_then(|()| OsRng.try_fill_bytes(&mut nonce))
+        {
+            return Err(EasyTotpError::Rng(Box::new(e)));
+        }
+
+        let mut data = Vec::with_capacity(HEADER_LEN);
+        data.extend_from_slice(MAGIC);
+        data.push(VERSION);
+        data.extend_from_slice(&salt);
+        data.extend_from_slice(&nonce);
+
+        let plaintext = self.encode_plaintext()?;
+        let cipher = new_cipher(password, &salt)?;
+        let payload = Payload {
+            msg: &plaintext,
+            aad: &data,
+        };
+        let ciphertext = match cipher.encrypt(&Nonce::from(nonce), payload) {
+            Ok(ciphertext) => ciphertext,
+            Err(e) => return Err(EasyTotpError::Encryption(e.to_string())),
+        };
+
+        data.extend_from_slice(&ciphertext);
+        Ok(data)
+    }
+
+    /// Decrypts data from `EasyTotp::to_encrypted`
+    ///
+    /// ## Errors
+    /// This function will return an error if the password is wrong, the data is malformed or was tampered with,
+    /// or the decrypted settings are invalid.
+    pub fn from_encrypted(data: &[u8], password: &str) -> Result<Self, EasyTotpError> {
+        if data.len() < HEADER_LEN || !data.starts_with(MAGIC) {
+            return Err(EasyTotpError::Encryption(String::from(
+                "data is not encrypted EasyTotp data",
+            )));
+        }
+
+        let (header, ciphertext) = data.split_at(HEADER_LEN);
+        if header[MAGIC.len()] != VERSION {
+            return Err(EasyTotpError::Encryption(format!(
+                "unsupported version {}",
+                header[MAGIC.len()]
+            )));
+        }
+        let (salt, nonce) = header[MAGIC.len() + 1..].split_at(SALT_LEN);
+        let Ok(nonce) = <[u8; NONCE_LEN]>::try_from(nonce) else {
+            return Err(malformed("truncated"));
+        };
+
+        let cipher = new_cipher(password, salt)?;
+        let payload = Payload {
+            msg: ciphertext,
+            aad: header,
+        };
+        let plaintext = match cipher.decrypt(&Nonce::from(nonce), payload) {
+            Ok(plaintext) => Zeroizing::new(plaintext),
+            Err(_) => {
+                return Err(EasyTotpError::Encryption(String::from(
+                    "wrong password, or the data was tampered with",
+                )));
+            }
+        };
+
+        Self::decode_plaintext(&plaintext)
+    }
+
+    /// Encrypts every setting with a password (see `to_encrypted`) and writes it to `path`, replacing any existing file
+    ///
+    /// On Unix, the file mode is set to `0o600`, so only the owner can read it. On other platforms, the file permissions
+    /// are left as they are, so restrict access to `path` yourself.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let path = std::env::temp_dir().join("easy_totp_doc_example.bin");
+    /// et.save_to_file(&path, "correct horse battery staple").unwrap();
+    ///
+    /// let loaded = EasyTotp::load_from_file(&path, "correct horse battery staple").unwrap();
+    /// assert_eq!(loaded, et);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the encryption fails (see `to_encrypted`), or if the file can't be written.
+    pub fn save_to_file(&self, path: &Path, password: &str) -> Result<(), EasyTotpError> {
+        let data = self.to_encrypted(password)?;
+
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        // New files are created owner-only, so the data is never readable by others, even briefly
+        #[cfg(unix)]
+        options.mode(0o600);
+
+        let mut file = match options.open(path) {
+            Ok(file) => file,
+            Err(e) => return Err(EasyTotpError::Io(e)),
+        };
+
+        // An existing file keeps its old mode when opened, so restrict it before writing
+        #[cfg(unix)]
+        if let Err(e) = file.set_permissions(fs::Permissions::from_mode(0o600)) {
+            return Err(EasyTotpError::Io(e));
+        }
+
+        match file.write_all(&data) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(EasyTotpError::Io(e)),
+        }
+    }
+
+    /// Reads and decrypts a file written by `save_to_file`
+    ///
+    /// ## Errors
+    /// This function will return an error if the file can't be read, or if the decryption fails (see `from_encrypted`).
+    pub fn load_from_file(path: &Path, password: &str) -> Result<Self, EasyTotpError> {
+        match fs::read(path) {
+            Ok(data) => Self::from_encrypted(&data, password),
+            Err(e) => Err(EasyTotpError::Io(e)),
+        }
+    }
+
+    /// Lays out the settings to encrypt; see the module documentation
+    fn encode_plaintext(&self) -> Result<Zeroizing<Vec<u8>>, EasyTotpError> {
+        let mut plaintext = Zeroizing::new(Vec::new());
+
+        write_bytes(&mut plaintext, &self.raw_secret, "secret")?;
+        match &self.issuer {
+            Some(issuer) => {
+                plaintext.push(1);
+                write_bytes(&mut plaintext, issuer.as_bytes(), "issuer")?;
+            }
+            None => plaintext.push(0),
+        }
+        write_bytes(&mut plaintext, self.account_name.as_bytes(), "account name")?;
+
+        plaintext.push(algorithm_number(self.algorithm));
+        // `with_digits` keeps this between 6 and 8
+        plaintext.push(u8::try_from(self.digits).unwrap_or(u8::MAX));
+        plaintext.extend_from_slice(&self.period.to_be_bytes());
+        plaintext.push(self.skew);
+        plaintext.push(ecc_number(self.qr_ecc));
+        plaintext.push(self.quiet_zone);
+        // Secrets can't be longer than 65535 bytes anyway, so a larger minimum is just as impossible to meet
+        plaintext.extend_from_slice(
+            &u16::try_from(self.min_secret_len)
+                .unwrap_or(u16::MAX)
+                .to_be_bytes(),
+        );
+        match &self.icon_url {
+            Some(icon_url) => {
+                plaintext.push(1);
+                write_bytes(&mut plaintext, icon_url.as_bytes(), "icon URL")?;
+            }
+            None => plaintext.push(0),
+        }
+
+        Ok(plaintext)
+    }
+
+    /// Reads settings laid out by `encode_plaintext`, validating them
+    fn decode_plaintext(plaintext: &[u8]) -> Result<Self, EasyTotpError> {
+        let mut reader = PlaintextReader { data: plaintext };
+
+        let raw_secret = Zeroizing::new(reader.read_bytes()?.to_vec());
+        let issuer = match reader.read_u8()? {
+            0 => None,
+            _ => Some(reader.read_string()?),
+        };
+        let account_name = reader.read_string()?;
+
+        let algorithm = match reader.read_u8()? {
+            1 => Algorithm::SHA1,
+            2 => Algorithm::SHA256,
+            3 => Algorithm::SHA512,
+            4 => Algorithm::Steam,
+            other => return Err(malformed(&format!("unknown algorithm {other}"))),
+        };
+        let digits = usize::from(reader.read_u8()?);
+        let period = u64::from_be_bytes(reader.read_array()?);
+        let skew = reader.read_u8()?;
+        let qr_ecc = match reader.read_u8()? {
+            0 => QREccLevel::Low,
+            1 => QREccLevel::Medium,
+            2 => QREccLevel::Quartile,
+            3 => QREccLevel::High,
+            other => return Err(malformed(&format!("unknown QR ECC level {other}"))),
+        };
+        let quiet_zone = reader.read_u8()?;
+        let min_secret_len = usize::from(u16::from_be_bytes(reader.read_array()?));
+        let icon_url = match reader.read_u8()? {
+            0 => None,
+            _ => Some(reader.read_string()?),
+        };
+
+        if !reader.data.is_empty() {
+            return Err(malformed("trailing bytes"));
+        }
+
+        let et = EasyTotp {
+            raw_secret,
+            issuer,
+            account_name,
+            algorithm,
+            skew,
+            qr_ecc,
+            quiet_zone,
+            min_secret_len,
+            ..EasyTotp::default()
+        }
+        .with_token_format(digits, period)?;
+        let et = match icon_url {
+            Some(icon_url) => et.with_icon_url(icon_url)?,
+            None => et,
+        };
+
+        // Building the `TOTP` instance checks the secret length and the issuer and account name
+        et.totp()?;
+
+        Ok(et)
+    }
+}
+
+/// Derives the 256-bit key from the password with Argon2id, and creates the cipher
+fn new_cipher(password: &str, salt: &[u8]) -> Result<ChaCha20Poly1305, EasyTotpError> {
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+
+    if let Err(e) = Argon2::default().hash_password_into(password.as_bytes(), salt, key.as_mut()) {
+        return Err(EasyTotpError::Encryption(format!(
+            "key derivation failed: {e}"
+        )));
+    }
+
+    match ChaCha20Poly1305::new_from_slice(key.as_ref()) {
+        Ok(cipher) => Ok(cipher),
+        Err(e) => Err(EasyTotpError::Encryption(e.to_string())),
+    }
+}
+
+/// Writes a `u16` length, followed by the bytes
+fn write_bytes(buffer: &mut Vec<u8>, bytes: &[u8], name: &str) -> Result<(), EasyTotpError> {
+    let Ok(len) = u16::try_from(bytes.len()) else {
+        return Err(EasyTotpError::InvalidSetting(format!(
+            "{name} is too long to encrypt ({} bytes)",
+            bytes.len()
+        )));
+    };
+
+    buffer.extend_from_slice(&len.to_be_bytes());
+    buffer.extend_from_slice(bytes);
+    Ok(())
+}
+
+/// Numbers the hash algorithm like the otpauth-migration payload, plus 4 for Steam Guard, which the payload has no number for
+fn algorithm_number(algorithm: Algorithm) -> u8 {
+    if algorithm == Algorithm::Steam {
+        4
+    } else {
+        migration::algorithm_number(algorithm)
+    }
+}
+
+/// Numbers the QR error correction level
+fn ecc_number(ecc: QREccLevel) -> u8 {
+    match ecc {
+        QREccLevel::Low => 0,
+        QREccLevel::Medium => 1,
+        QREccLevel::Quartile => 2,
+        QREccLevel::High => 3,
+    }
+}
+
+/// Error for decrypted data that doesn't follow the plaintext layout
+fn malformed(message: &str) -> EasyTotpError {
+    EasyTotpError::Encryption(format!("decrypted data is malformed: {message}"))
+}
+
+/// Reads the fields of the decrypted plaintext in order
+struct PlaintextReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> PlaintextReader<'a> {
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], EasyTotpError> {
+        let Some((bytes, rest)) = self.data.split_first_chunk::<N>() else {
+            return Err(malformed("truncated"));
+        };
+        self.data = rest;
+        Ok(*bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, EasyTotpError> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    fn read_bytes(&mut self) -> Result<&'a [u8], EasyTotpError> {
+        let len = usize::from(u16::from_be_bytes(self.read_array()?));
+        if self.data.len() < len {
+            return Err(malformed("truncated"));
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn read_string(&mut self) -> Result<String, EasyTotpError> {
+        match String::from_utf8(self.read_bytes()?.to_vec()) {
+            Ok(string) => Ok(string),
+            Err(_) => Err(malformed("text is not valid UTF-8")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account() -> EasyTotp {
+        EasyTotp::from_base32_secret(
+            "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ",
+            Some(String::from("McCormick")),
+            String::from("test@test-email.com"),
+        )
+        .unwrap()
+        .with_algorithm(Algorithm::SHA256)
+        .with_digits(8)
+        .unwrap()
+        .with_period(60)
+        .unwrap()
+        .with_skew(2)
+        .with_qr_ecc(QREccLevel::High)
+        .with_quiet_zone(2)
+        .with_icon_url("https://example.com/icon.png")
+        .unwrap()
+    }
+
+    #[test]
+    fn test_encrypted_round_trip() {
+        let et = account();
+        let encrypted = et.to_encrypted("hunter2").unwrap();
+
+        assert!(encrypted.starts_with(MAGIC));
+        // The secret must not appear in the output
+        assert!(
+            !encrypted
+                .windows(et.raw_secret.len())
+                .any(|window| window == et.raw_secret.as_slice())
+        );
+
+        let decrypted = EasyTotp::from_encrypted(&encrypted, "hunter2").unwrap();
+        assert_eq!(decrypted, et);
+
+        // Fresh salt and nonce every time
+        assert_ne!(et.to_encrypted("hunter2").unwrap(), encrypted);
+
+        let no_issuer = EasyTotp {
+            issuer: None,
+            icon_url: None,
+            ..account()
+        };
+        let encrypted = no_issuer.to_encrypted("").unwrap();
+        assert_eq!(EasyTotp::from_encrypted(&encrypted, "").unwrap(), no_issuer);
+
+        let steam = account().with_algorithm(Algorithm::Steam);
+        let encrypted = steam.to_encrypted("hunter2").unwrap();
+        assert_eq!(
+            EasyTotp::from_encrypted(&encrypted, "hunter2").unwrap(),
+            steam
+        );
+    }
+
+    #[test]
+    fn test_encrypted_wrong_password_or_tampering() {
+        let encrypted = account().to_encrypted("hunter2").unwrap();
+
+        assert!(matches!(
+            EasyTotp::from_encrypted(&encrypted, "hunter3"),
+            Err(EasyTotpError::Encryption(_))
+        ));
+
+        // Flipping a bit of the header (associated data) or the ciphertext fails authentication
+        for index in [MAGIC.len() + 1, encrypted.len() - 1] {
+            let mut tampered = encrypted.clone();
+            tampered[index] ^= 1;
+            assert!(matches!(
+                EasyTotp::from_encrypted(&tampered, "hunter2"),
+                Err(EasyTotpError::Encryption(_))
+            ));
+        }
+
+        assert!(matches!(
+            EasyTotp::from_encrypted(b"EZTOTP", "hunter2"),
+            Err(EasyTotpError::Encryption(_))
+        ));
+        assert!(matches!(
+            EasyTotp::from_encrypted(&encrypted[1..], "hunter2"),
+            Err(EasyTotpError::Encryption(_))
+        ));
+    }
+
+    #[test]
+    fn test_save_and_load_file() {
+        let et = account();
+        let path = std::env::temp_dir().join(format!(
+            "easy_totp_test_save_and_load_{}.bin",
+            std::process::id()
+        ));
+
+        // Start from a world-readable file, to check that its mode is restricted too
+        fs::write(&path, b"old contents").unwrap();
+        #[cfg(unix)]
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        et.save_to_file(&path, "hunter2").unwrap();
+
+        #[cfg(unix)]
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+
+        let loaded = EasyTotp::load_from_file(&path, "hunter2").unwrap();
+        assert_eq!(loaded, et);
+        for timestamp in [0, 59, 1_111_111_109, 2_000_000_000] {
+            assert_eq!(
+                loaded.generate_token_at(timestamp).unwrap(),
+                et.generate_token_at(timestamp).unwrap()
+            );
+        }
+
+        assert!(matches!(
+            EasyTotp::load_from_file(&path, "hunter3"),
+            Err(EasyTotpError::Encryption(_))
+        ));
+
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            EasyTotp::load_from_file(&path, "hunter2"),
+            Err(EasyTotpError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,596 @@
+//! Counter-based one-time passwords (HOTP, RFC 4226)
+
+use crate::{
+    DEFAULT_SECRET_LEN, EasyTotpError, QRAmbiguousWidth, QRColorMode, QRSize, QrOptions,
+    decode_base32_secret, qr_instructions, qr_svg, qr_text_lines, random_secret, redacted_secret,
+    render_qr_image, terminal_width, validate_label, validate_names_not_blank, write_lines,
+};
+
+use constant_time_eq::constant_time_eq;
+use image::codecs::png::PngEncoder;
+#[cfg(feature = "plaintext-serde")]
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::io::stdout;
+use totp_rs::{Algorithm, Secret, TOTP};
+use zeroize::Zeroizing;
+
+/// `EasyHotp` generates and verifies counter-based one-time passwords (HOTP, RFC 4226)
+///
+/// Each code is derived from an incrementing counter instead of the wall clock, as used by many hardware tokens and legacy systems.
+/// Store the counter alongside the secret, and advance it past the matched value after every successful verification.
+///
+/// Secrets are zeroized on drop, exactly like [`EasyTotp`](crate::EasyTotp).
+#[derive(Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "plaintext-serde", derive(Deserialize, Serialize))]
+pub struct EasyHotp {
+    raw_secret: Zeroizing<Vec<u8>>,
+    issuer: Option<String>,
+    account_name: String,
+    algorithm: Algorithm,
+    digits: usize,
+    counter: u64,
+    look_ahead: u8,
+}
+
+impl fmt::Debug for EasyHotp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EasyHotp")
+            .field("raw_secret", &redacted_secret(&self.raw_secret))
+            .field("issuer", &self.issuer)
+            .field("account_name", &self.account_name)
+            .field("algorithm", &self.algorithm)
+            .field("digits", &self.digits)
+            .field("counter", &self.counter)
+            .field("look_ahead", &self.look_ahead)
+            .finish()
+    }
+}
+
+impl Default for EasyHotp {
+    fn default() -> Self {
+        EasyHotp {
+            raw_secret: Zeroizing::new(Vec::new()),
+            issuer: None,
+            account_name: String::new(),
+            // HOTP hardware tokens almost universally implement SHA1 only
+            algorithm: Algorithm::SHA1,
+            digits: 6,
+            counter: 0,
+            look_ahead: 10,
+        }
+    }
+}
+
+impl EasyHotp {
+    /// The largest look-ahead `verify_hotp` accepts. Each value costs an HMAC when the code is wrong, so this bounds the work per attempt.
+    pub const MAX_LOOK_AHEAD: u64 = 1_000;
+
+    /// Creates a new `EasyHotp` instance with a randomly generated secret key
+    ///
+    /// ```rust
+    /// use easy_totp::EasyHotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let eh = EasyHotp::new(issuer, account_name).unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return `EasyTotpError::InvalidSetting` if the account name, or the issuer if given, is empty or whitespace-only
+    /// or contains a `:`, and `EasyTotpError::Rng` if the random number generator fails to generate bytes for the secret key.
+    pub fn new(issuer: Option<String>, account_name: String) -> Result<Self, EasyTotpError> {
+        validate_label(issuer.as_deref(), &account_name)?;
+        validate_names_not_blank(issuer.as_deref(), &account_name)?;
+
+        Ok(EasyHotp {
+            raw_secret: random_secret(DEFAULT_SECRET_LEN)?,
+            issuer,
+            account_name,
+            ..Default::default()
+        })
+    }
+
+    /// Creates a new `EasyHotp` instance from an existing base32-encoded secret key
+    ///
+    /// Accepts secrets as authenticator apps show them for manual entry: see `EasyTotp::from_base32_secret`.
+    ///
+    /// BEWARE: handle secrets with caution!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyHotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let eh = EasyHotp::from_base32_secret("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ", issuer, account_name).unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the secret is empty or is not valid base32, or if the issuer or account name contain a `:`.
+    pub fn from_base32_secret(
+        secret: &str,
+        issuer: Option<String>,
+        account_name: String,
+    ) -> Result<Self, EasyTotpError> {
+        validate_label(issuer.as_deref(), &account_name)?;
+
+        Ok(EasyHotp {
+            raw_secret: decode_base32_secret(secret)?,
+            issuer,
+            account_name,
+            ..Default::default()
+        })
+    }
+
+    /// Returns the secret key as an unpadded base32 string, for manual entry into an authenticator app
+    ///
+    /// BEWARE: output contains secret!!
+    #[must_use]
+    pub fn secret_base32(&self) -> String {
+        match &Secret::Raw(self.raw_secret.to_vec()).to_encoded() {
+            Secret::Encoded(encoded) => encoded.clone(),
+            Secret::Raw(_) => unreachable!("Secret::to_encoded always returns Secret::Encoded"),
+        }
+    }
+
+    /// Sets the hash algorithm used for code generation, verification, and the otpauth URI
+    ///
+    /// Defaults to `Algorithm::SHA1`, the only algorithm most HOTP hardware tokens support.
+    #[must_use]
+    pub fn with_algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Sets the number of digits in each generated code
+    ///
+    /// Defaults to 6. RFC 4226 allows between 6 and 8 digits.
+    ///
+    /// ## Errors
+    /// This function will return an error if `digits` is not between 6 and 8 (inclusive).
+    pub fn with_digits(mut self, digits: usize) -> Result<Self, EasyTotpError> {
+        if !(6..=8).contains(&digits) {
+            return Err(EasyTotpError::InvalidSetting(format!(
+                "digits must be between 6 and 8, not {digits}"
+            )));
+        }
+
+        self.digits = digits;
+        Ok(self)
+    }
+
+    /// Sets the initial counter advertised to authenticator apps through the otpauth URI
+    ///
+    /// Defaults to 0. It only affects `to_otpauth_uri` and the QR codes; generation and verification take the counter explicitly.
+    #[must_use]
+    pub fn with_counter(mut self, counter: u64) -> Self {
+        self.counter = counter;
+        self
+    }
+
+    /// Sets the look-ahead window: the number of counter values after the expected one that are also accepted
+    ///
+    /// Defaults to 10, which lets a user press the button on their token a few times without getting out of sync.
+    ///
+    /// BEWARE: a larger look-ahead weakens security!! Every extra value is another code an attacker may guess.
+    #[must_use]
+    pub fn with_look_ahead(mut self, steps: u8) -> Self {
+        self.look_ahead = steps;
+        self
+    }
+
+    /// Creates a `TOTP` instance with a 1-second step, so that `TOTP::generate(counter)` computes HOTP for `counter`
+    fn engine(&self) -> Result<TOTP, EasyTotpError> {
+        validate_label(self.issuer.as_deref(), &self.account_name)?;
+
+        if self.algorithm == Algorithm::Steam {
+            return Err(EasyTotpError::InvalidSetting(String::from(
+                "Steam Guard tokens are time-based, not counter-based",
+            )));
+        }
+
+        let result = TOTP::new(
+            self.algorithm,
+            self.digits,
+            0,
+            1,
+            self.raw_secret.to_vec(),
+            self.issuer.clone(),
+            self.account_name.clone(),
+        );
+
+        match result {
+            Ok(totp) => Ok(totp),
+            Err(e) => Err(EasyTotpError::TotpConstruction(e)),
+        }
+    }
+
+    /// Returns the `otpauth://hotp/...` provisioning URI, including the initial `counter` parameter
+    ///
+    /// BEWARE: URI contains secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyHotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let eh = EasyHotp::new(issuer, account_name).unwrap();
+    ///
+    /// let uri = eh.to_otpauth_uri().unwrap();
+    /// assert!(uri.starts_with("otpauth://hotp/McCormick:test%40test-email.com?secret="));
+    /// assert!(uri.ends_with("&counter=0"));
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the secret is too short, or the issuer or account name contain a `:`
+    /// (which separates them in the `issuer:account_name` label).
+    pub fn to_otpauth_uri(&self) -> Result<String, EasyTotpError> {
+        // Build the engine for its validation of the secret, issuer, and account name
+        self.engine()?;
+
+        let account_name = urlencoding::encode(&self.account_name);
+        let mut params = vec![format!("secret={}", self.secret_base32())];
+        if self.digits != 6 {
+            params.push(format!("digits={}", self.digits));
+        }
+        if self.algorithm != Algorithm::SHA1 {
+            params.push(format!("algorithm={}", self.algorithm));
+        }
+        let label = match &self.issuer {
+            Some(issuer) => {
+                let issuer = urlencoding::encode(issuer);
+                params.push(format!("issuer={issuer}"));
+                format!("{issuer}:{account_name}")
+            }
+            None => account_name.into_owned(),
+        };
+        params.push(format!("counter={}", self.counter));
+
+        Ok(format!("otpauth://hotp/{label}?{}", params.join("&")))
+    }
+
+    /// Generates the HOTP code for `counter`
+    ///
+    /// ```rust
+    /// use easy_totp::EasyHotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let eh = EasyHotp::new(issuer, account_name).unwrap();
+    ///
+    /// let code = eh.generate_at_counter(0).unwrap();
+    /// assert_eq!(code.len(), 6);
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the HOTP instance cannot be created.
+    pub fn generate_at_counter(&self, counter: u64) -> Result<String, EasyTotpError> {
+        Ok(self.engine()?.generate(counter))
+    }
+
+    /// Verifies a user-submitted HOTP code against `counter` and the look-ahead window after it
+    ///
+    /// Returns the counter value that matched, or `None`. On a match, store `matched + 1` as the next expected counter
+    /// so that the same code can never be accepted twice. Codes are compared in constant time.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyHotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let eh = EasyHotp::new(issuer, account_name).unwrap();
+    ///
+    /// let code = eh.generate_at_counter(3).unwrap();
+    /// assert_eq!(eh.verify_at_counter(&code, 0).unwrap(), Some(3));
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the HOTP instance cannot be created.
+    pub fn verify_at_counter(
+        &self,
+        token: &str,
+        counter: u64,
+    ) -> Result<Option<u64>, EasyTotpError> {
+        self.find_counter(token, counter, u64::from(self.look_ahead))
+    }
+
+    /// Verifies a user-submitted HOTP code against `server_counter ..= server_counter + look_ahead`, resynchronizing on a match
+    ///
+    /// The standard HOTP resync: clients drift ahead whenever the user generates a code without using it, so codes up to
+    /// `look_ahead` counter values ahead are accepted. On a match, `server_counter` is advanced past the matched value,
+    /// so that neither this code nor any earlier one is accepted again; store it before responding. On a mismatch it is left alone.
+    /// This ignores the look-ahead set with `with_look_ahead`.
+    ///
+    /// BEWARE: a larger look-ahead weakens security!! Every extra value is another code an attacker may guess.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyHotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let eh = EasyHotp::new(issuer, account_name).unwrap();
+    ///
+    /// let mut server_counter = 0;
+    /// let code = eh.generate_at_counter(3).unwrap();
+    /// assert!(eh.verify_hotp(&code, &mut server_counter, 5).unwrap());
+    /// assert_eq!(server_counter, 4);
+    /// assert!(!eh.verify_hotp(&code, &mut server_counter, 5).unwrap());
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return `EasyTotpError::InvalidSetting` if `look_ahead` is more than `EasyHotp::MAX_LOOK_AHEAD`,
+    /// and an error if the HOTP instance cannot be created.
+    pub fn verify_hotp(
+        &self,
+        token: &str,
+        server_counter: &mut u64,
+        look_ahead: u64,
+    ) -> Result<bool, EasyTotpError> {
+        if look_ahead > EasyHotp::MAX_LOOK_AHEAD {
+            return Err(EasyTotpError::InvalidSetting(format!(
+                "look-ahead must be at most {}, not {look_ahead}",
+                EasyHotp::MAX_LOOK_AHEAD
+            )));
+        }
+
+        // The last counter value has no successor to advance to, so accepting it would allow a replay
+        match self
+            .find_counter(token, *server_counter, look_ahead)?
+            .and_then(|matched| matched.checked_add(1))
+        {
+            Some(next) => {
+                *server_counter = next;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Finds the counter value in `counter ..= counter + look_ahead` whose code is `token`, if any
+    fn find_counter(
+        &self,
+        token: &str,
+        counter: u64,
+        look_ahead: u64,
+    ) -> Result<Option<u64>, EasyTotpError> {
+        if token.len() != self.digits || !token.chars().all(|c| c.is_ascii_digit()) {
+            return Ok(None);
+        }
+
+        let engine = self.engine()?;
+        let last = counter.saturating_add(look_ahead);
+
+        Ok((counter..=last)
+            .find(|&c| constant_time_eq(engine.generate(c).as_bytes(), token.as_bytes())))
+    }
+
+    /// Creates a new PNG with a QR code of the `otpauth://hotp/...` URI
+    ///
+    /// BEWARE: PNG image contains secret!!
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation or image processing fails.
+    pub fn create_qr_png(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut buffer = Vec::new();
+        render_qr_image(&self.to_otpauth_uri()?, QrOptions::default(), 8)?
+            .write_with_encoder(PngEncoder::new(&mut buffer))?;
+
+        Ok(buffer)
+    }
+
+    /// Creates a new SVG document with a QR code of the `otpauth://hotp/...` URI
+    ///
+    /// BEWARE: SVG image contains secret!!
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation fails.
+    pub fn create_qr_svg(&self) -> Result<String, EasyTotpError> {
+        qr_svg(&self.to_otpauth_uri()?, QrOptions::default())
+    }
+
+    /// Generates QR code text for terminal display, but does not actually print it.
+    ///
+    /// The QR code is scaled to fit the current terminal width, or 80 columns when it can't be detected.
+    ///
+    /// BEWARE: output contains secret!!
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation or image processing fails.
+    pub fn qr_text(&self, size: QRSize, mode: QRColorMode) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut lines = qr_text_lines(
+            &self.to_otpauth_uri()?,
+            QrOptions::default(),
+            size,
+            mode,
+            terminal_width(),
+            QRAmbiguousWidth::Narrow,
+        )?;
+        lines.extend(qr_instructions("HOTP"));
+
+        Ok(lines)
+    }
+
+    /// Print the QR code to the terminal
+    ///
+    /// BEWARE: terminal will display secret!!
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation or terminal rendering fails.
+    pub fn print_qr_to_terminal(
+        &self,
+        size: QRSize,
+        mode: QRColorMode,
+    ) -> Result<(), Box<dyn Error>> {
+        write_lines(&mut stdout().lock(), &self.qr_text(size, mode)?)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The secret used by the test vectors in RFC 4226 Appendix D
+    fn rfc_hotp() -> EasyHotp {
+        EasyHotp {
+            raw_secret: Zeroizing::new(b"12345678901234567890".to_vec()),
+            issuer: Some(String::from("McCormick")),
+            account_name: String::from("test@test-email.com"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rfc4226_vectors() {
+        let eh = rfc_hotp();
+        let expected = [
+            "755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583",
+            "399871", "520489",
+        ];
+
+        for (counter, code) in (0..).zip(expected) {
+            assert_eq!(eh.generate_at_counter(counter).unwrap(), code);
+        }
+    }
+
+    #[test]
+    fn test_verify_look_ahead() {
+        let eh = rfc_hotp().with_look_ahead(2);
+
+        assert_eq!(eh.verify_at_counter("755224", 0).unwrap(), Some(0));
+        assert_eq!(eh.verify_at_counter("359152", 0).unwrap(), Some(2));
+        // Just outside the window, and behind the expected counter
+        assert_eq!(eh.verify_at_counter("969429", 0).unwrap(), None);
+        assert_eq!(eh.verify_at_counter("755224", 1).unwrap(), None);
+        assert_eq!(eh.verify_at_counter("garbage", 0).unwrap(), None);
+
+        // The window must not overflow at the top of the counter range
+        assert!(eh.verify_at_counter("000000", u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_verify_hotp_resync() {
+        // The configured look-ahead doesn't apply
+        let eh = rfc_hotp().with_look_ahead(0);
+        let mut server_counter = 0;
+
+        // The client drifted 3 ahead: accepted, and the server catches up
+        assert!(eh.verify_hotp("969429", &mut server_counter, 3).unwrap());
+        assert_eq!(server_counter, 4);
+
+        // Neither the same code nor earlier ones are accepted again
+        assert!(!eh.verify_hotp("969429", &mut server_counter, 3).unwrap());
+        assert!(!eh.verify_hotp("359152", &mut server_counter, 3).unwrap());
+        assert_eq!(server_counter, 4);
+
+        // Just outside the window, then just inside it
+        assert!(!eh.verify_hotp("399871", &mut server_counter, 3).unwrap());
+        assert_eq!(server_counter, 4);
+        assert!(eh.verify_hotp("399871", &mut server_counter, 4).unwrap());
+        assert_eq!(server_counter, 9);
+
+        // No look-ahead only accepts the expected counter
+        assert!(eh.verify_hotp("520489", &mut server_counter, 0).unwrap());
+        assert_eq!(server_counter, 10);
+        assert!(!eh.verify_hotp("garbage", &mut server_counter, 10).unwrap());
+        assert_eq!(server_counter, 10);
+
+        // The window must not overflow, and the last counter value can't be advanced past
+        let max = EasyHotp::MAX_LOOK_AHEAD;
+        let mut server_counter = u64::MAX - 1;
+        let code = eh.generate_at_counter(u64::MAX - 1).unwrap();
+        assert!(eh.verify_hotp(&code, &mut server_counter, max).unwrap());
+        assert_eq!(server_counter, u64::MAX);
+        let code = eh.generate_at_counter(u64::MAX).unwrap();
+        assert!(!eh.verify_hotp(&code, &mut server_counter, max).unwrap());
+        assert_eq!(server_counter, u64::MAX);
+    }
+
+    #[test]
+    fn test_verify_hotp_max_look_ahead() {
+        let eh = rfc_hotp();
+        let mut server_counter = 0;
+
+        // Up to the cap works, even for a code at its far end
+        let code = eh.generate_at_counter(EasyHotp::MAX_LOOK_AHEAD).unwrap();
+        assert!(
+            eh.verify_hotp(&code, &mut server_counter, EasyHotp::MAX_LOOK_AHEAD)
+                .unwrap()
+        );
+        assert_eq!(server_counter, EasyHotp::MAX_LOOK_AHEAD + 1);
+
+        // Anything above it is rejected up front, rather than scanning a huge window, and the counter is left alone
+        for look_ahead in [EasyHotp::MAX_LOOK_AHEAD + 1, u64::MAX] {
+            assert!(matches!(
+                eh.verify_hotp("000000", &mut server_counter, look_ahead),
+                Err(EasyTotpError::InvalidSetting(_))
+            ));
+        }
+        assert_eq!(server_counter, EasyHotp::MAX_LOOK_AHEAD + 1);
+    }
+
+    #[test]
+    fn test_hotp_uri_and_qr() {
+        let eh = rfc_hotp().with_counter(42).with_digits(8).unwrap();
+        let uri = eh.to_otpauth_uri().unwrap();
+        assert_eq!(
+            uri,
+            format!(
+                "otpauth://hotp/McCormick:test%40test-email.com?secret={}&digits=8&issuer=McCormick&counter=42",
+                eh.secret_base32()
+            )
+        );
+
+        let img = image::load_from_memory(&eh.create_qr_png().unwrap())
+            .unwrap()
+            .to_luma8();
+        let mut img = rqrr::PreparedImage::prepare(img);
+        let grids = img.detect_grids();
+        assert_eq!(grids.len(), 1);
+        let (_, content) = grids[0].decode().unwrap();
+        assert_eq!(content, uri);
+
+        assert!(eh.create_qr_svg().unwrap().starts_with("<svg"));
+        assert!(
+            eh.qr_text(QRSize::Full, QRColorMode::Direct)
+                .unwrap()
+                .iter()
+                .any(|line| line.contains("set up HOTP"))
+        );
+    }
+
+    #[test]
+    fn test_hotp_label_validation() {
+        // A `:` would make the `issuer:account_name` label ambiguous
+        for (issuer, account_name) in [
+            (Some("Mc:Cormick"), "test@test-email.com"),
+            (Some("McCormick"), "test:test-email.com"),
+            (None, "McCormick:test@test-email.com"),
+        ] {
+            let issuer = issuer.map(String::from);
+            let account_name = String::from(account_name);
+            assert!(matches!(
+                EasyHotp::from_base32_secret(
+                    "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ",
+                    issuer.clone(),
+                    account_name.clone()
+                ),
+                Err(EasyTotpError::InvalidSetting(_))
+            ));
+            assert!(matches!(
+                EasyHotp::new(issuer, account_name),
+                Err(EasyTotpError::InvalidSetting(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_hotp_debug_redacts_secret() {
+        let eh = rfc_hotp();
+        let debug = format!("{eh:?}");
+        assert!(debug.contains("<redacted 20 bytes>"));
+        assert!(!debug.contains(&eh.secret_base32()));
+    }
+}
@@ -1,3 +1,4 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(missing_docs)]
 #![deny(unsafe_code)]
 #![doc = include_str!("../README.md")]
@@ -44,7 +45,7 @@
 //!         println!("QR code saved as 'qr_code.png'");
 //!     }
 //!     Err(e) => {
-//!         panic!("Error creating QR code: {:?}", e);
+//!         panic!("Error creating QR code: {e:?}");
 //!     }
 //! }
 //! ```
@@ -62,54 +63,210 @@
 //! let token = et.generate_token().unwrap();
 //! ```
 //!
+//! ## Feature flags
+//!
+//! - `std` (on by default): everything except the `embedded` module. Without it, the crate is `no_std` and only needs
+//!   `alloc`, e.g. to generate tokens on a microcontroller with `embedded::TotpCore`.
+//! - `rayon` (off by default): generates the QR codes of `batch_qr_pngs` and `contact_sheet` in parallel.
+//! - `tokio` (off by default): async versions of `verify_token`, `save_to_file`, and `load_from_file`, which run on Tokio's
+//!   blocking thread pool so the key derivation of the encrypted files doesn't stall the async runtime.
+//! - `serde` (off by default): derives `Serialize` and `Deserialize` for `EasyTotpMetadata`, which holds no secrets.
+//! - `plaintext-serde` (off by default): derives `Serialize` and `Deserialize` for `EasyTotp`, `EasyHotp`, and the QR enums.
+//!   **BEWARE: this writes the secret key in plaintext**, so that e.g. `serde_json::to_string(&et)` leaks it into whatever
+//!   stores the output. It is opt-in so that enabling it is a conscious choice. To persist secrets, prefer
+//!   `EasyTotp::to_encrypted` and `EasyTotp::from_encrypted`, which are always available.
+//! - `snapshot` (off by default): `EasyTotp::qr_snapshot`, a text dump of the QR matrix and terminal renderings for snapshot
+//!   tests, e.g. with `insta`.
+//!
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+use totp_rs::qrcodegen_image::qrcodegen::{QrCode, QrCodeEcc};
+#[cfg(feature = "std")]
+use totp_rs::{Secret, TOTP};
 
-use totp_rs::{Algorithm, Secret, TOTP};
+#[cfg(feature = "std")]
+mod batch;
+#[cfg(feature = "std")]
+mod builder;
+pub mod embedded;
+#[cfg(feature = "std")]
+mod encryption;
+#[cfg(feature = "std")]
+mod hotp;
+#[cfg(feature = "std")]
+mod migration;
+#[cfg(feature = "tokio")]
+mod nonblocking;
+#[cfg(feature = "std")]
+mod recovery;
+#[cfg(feature = "std")]
+mod rotation;
 
+#[cfg(feature = "std")]
+pub use batch::{batch_qr_pngs, contact_sheet};
+#[cfg(feature = "std")]
+pub use builder::EasyTotpBuilder;
+#[cfg(feature = "std")]
+pub use hotp::EasyHotp;
+#[cfg(feature = "std")]
+pub use image::{DynamicImage, GrayImage, ImageFormat};
+#[cfg(feature = "std")]
+pub use migration::{
+    DedupReport, create_migration_qr_png, dedup_accounts, export_migration_uri,
+    import_migration_uri, import_migration_uri_dedup,
+};
+#[cfg(feature = "std")]
+pub use recovery::{generate_recovery_codes, hash_recovery_code, verify_recovery_code};
+#[cfg(feature = "std")]
+pub use rotation::{verify_token_any, verify_token_any_at};
+#[cfg(feature = "std")]
+pub use totp_rs::Algorithm;
+
+#[cfg(feature = "std")]
 use base64::{Engine as _, engine::general_purpose};
-use rand::{TryRngCore, rngs::OsRng};
+#[cfg(feature = "std")]
+use constant_time_eq::constant_time_eq;
+#[cfg(feature = "std")]
+use image::codecs::png::PngEncoder;
+#[cfg(feature = "std")]
+use image::{Rgb, RgbImage, Rgba, RgbaImage, imageops};
+#[cfg(feature = "std")]
+use rand::{TryCryptoRng, rngs::OsRng};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
 use std::error::Error;
-use std::fmt::{self};
+#[cfg(feature = "std")]
+use std::fmt::{self, Write as _};
+#[cfg(feature = "std")]
 use std::io::{Cursor, Write, stdout};
+#[cfg(feature = "std")]
+use std::sync::{Arc, OnceLock};
+#[cfg(feature = "std")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+#[cfg(feature = "std")]
+use unicode_width::UnicodeWidthChar;
+#[cfg(feature = "std")]
+use zeroize::{Zeroize, Zeroizing};
 
-#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
-struct EasyTotpError(String);
+/// `EasyTotpError` describes what went wrong inside `EasyTotp`
+///
+/// Match on the variant to tell failures apart programmatically; use [`Error::source`] to reach the underlying cause, where there is one.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum EasyTotpError {
+    /// The secret key is missing or could not be parsed
+    SecretParse(String),
+    /// The otpauth URI is malformed or unsupported
+    InvalidUri(String),
+    /// A setting is outside of its valid range
+    InvalidSetting(String),
+    /// `totp_rs` rejected the parameters used to build the `TOTP` instance
+    TotpConstruction(totp_rs::TotpUrlError),
+    /// The QR code could not be generated, e.g. because the otpauth URI is too long
+    QrGeneration(String),
+    /// The QR code image could not be decoded or processed
+    ImageDecode(Box<dyn Error + Send + Sync>),
+    /// The random number generator failed to produce a secret key
+    Rng(Box<dyn Error + Send + Sync>),
+    /// The system time is unavailable or before the Unix epoch
+    SystemTime(std::time::SystemTimeError),
+    /// Encrypted data could not be decrypted, e.g. because the password is wrong or the data was tampered with
+    Encryption(String),
+    /// Reading or writing a file failed
+    Io(std::io::Error),
+    /// The QR code is denser than the given maximum version, and may be hard to scan; see `EasyTotp::check_qr_density`
+    QrTooDense {
+        /// The QR code version (1 to 40) the otpauth URI needs
+        version: u8,
+        /// The maximum version that was allowed
+        max_version: u8,
+    },
+    /// A freshly generated token failed verification; see `EasyTotp::self_check`
+    SelfCheck(String),
+}
 
+#[cfg(feature = "std")]
 impl fmt::Display for EasyTotpError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "EasyTotp encountered an error: {}", self.0)
+        write!(f, "EasyTotp encountered an error: ")?;
+
+        match self {
+            EasyTotpError::SecretParse(message) => write!(f, "invalid secret key: {message}"),
+            EasyTotpError::InvalidUri(message) => write!(f, "invalid otpauth URI: {message}"),
+            EasyTotpError::InvalidSetting(message) => write!(f, "invalid setting: {message}"),
+            EasyTotpError::TotpConstruction(e) => write!(f, "error creating TOTP instance: {e}"),
+            EasyTotpError::QrGeneration(message) => {
+                write!(f, "error creating QR code data: {message}")
+            }
+            EasyTotpError::ImageDecode(e) => write!(f, "error processing QR code image: {e}"),
+            EasyTotpError::Rng(e) => write!(f, "random number generator failed: {e}"),
+            EasyTotpError::SystemTime(e) => write!(f, "system time is unavailable: {e}"),
+            EasyTotpError::Encryption(message) => write!(f, "encryption error: {message}"),
+            EasyTotpError::Io(e) => write!(f, "I/O error: {e}"),
+            EasyTotpError::QrTooDense {
+                version,
+                max_version,
+            } => {
+                let modules = qr_modules(*version);
+                write!(
+                    f,
+                    "QR code version {version} ({modules}x{modules} modules) is denser than version {max_version}; \
+                     shorten the issuer or account name, or lower the QR error correction level"
+                )
+            }
+            EasyTotpError::SelfCheck(message) => write!(f, "self-check failed: {message}"),
+        }
     }
 }
 
-impl Error for EasyTotpError {}
-
-impl EasyTotpError {
-    fn new(message: &str) -> Self {
-        EasyTotpError(message.to_string())
+#[cfg(feature = "std")]
+impl Error for EasyTotpError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EasyTotpError::TotpConstruction(e) => Some(e),
+            EasyTotpError::ImageDecode(e) | EasyTotpError::Rng(e) => Some(e.as_ref()),
+            EasyTotpError::SystemTime(e) => Some(e),
+            EasyTotpError::Io(e) => Some(e),
+            EasyTotpError::SecretParse(_)
+            | EasyTotpError::InvalidUri(_)
+            | EasyTotpError::InvalidSetting(_)
+            | EasyTotpError::QrGeneration(_)
+            | EasyTotpError::Encryption(_)
+            | EasyTotpError::QrTooDense { .. }
+            | EasyTotpError::SelfCheck(_) => None,
+        }
     }
 }
 
-/// `TerminalQRSize` defines whether the QR code is rendered in full size or mini size for terminal display
+/// `QRSize` defines whether the QR code is rendered in full size or mini size for terminal display
 /// Full size uses standard block characters, while mini size uses half-block characters to reduce height
+#[cfg(feature = "std")]
 #[repr(u8)]
-#[derive(
-    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
-)]
-pub enum TerminalQRSize {
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "plaintext-serde", derive(Deserialize, Serialize))]
+pub enum QRSize {
     /// Full size QR code using standard block characters
     #[default]
     Full = 0,
     /// Mini size QR code using half-block characters
-    #[allow(dead_code)]
     Mini = 1,
 }
 
+/// Former name of [`QRSize`]
+#[cfg(feature = "std")]
+#[deprecated(since = "0.6.0", note = "renamed to `QRSize`")]
+pub type TerminalQRSize = QRSize;
+
 /// `QRColorMode` defines whether the QR code is rendered in direct or inverted colors
 /// For light mode, use `Direct`; for dark mode, use `Inverted`. Some QR scanners may still be able to read either way.
+#[cfg(feature = "std")]
 #[repr(u8)]
-#[derive(
-    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
-)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "plaintext-serde", derive(Deserialize, Serialize))]
 pub enum QRColorMode {
     /// Direct colors (black on white during light mode, vice versa for dark mode)
     Direct = 0,
@@ -118,215 +275,728 @@ pub enum QRColorMode {
     Inverted = 1,
 }
 
+/// `QREccLevel` defines the minimum error-correction level of generated QR codes
+/// Higher levels survive more smudging or damage (e.g. on printed materials), at the cost of a denser QR code.
+/// The level is raised automatically when that fits without making the QR code any larger.
+#[cfg(feature = "std")]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "plaintext-serde", derive(Deserialize, Serialize))]
+pub enum QREccLevel {
+    /// Recovers from about 7% damage
+    Low = 0,
+    /// Recovers from about 15% damage
+    #[default]
+    Medium = 1,
+    /// Recovers from about 25% damage
+    Quartile = 2,
+    /// Recovers from about 30% damage
+    High = 3,
+}
+
+/// `QRAmbiguousWidth` describes how wide the terminal draws East Asian "ambiguous width" characters, such as block characters
+/// Most terminals draw them one cell wide, but terminals configured for CJK locales often draw them two cells wide,
+/// which stretches the QR code and makes it unscannable. For those, the QR code falls back to `##` and double spaces.
+#[cfg(feature = "std")]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "plaintext-serde", derive(Deserialize, Serialize))]
+pub enum QRAmbiguousWidth {
+    /// Ambiguous characters take one cell
+    #[default]
+    Narrow = 0,
+    /// Ambiguous characters take two cells, as in many CJK terminals
+    Wide = 1,
+}
+
+#[cfg(feature = "std")]
+impl QRAmbiguousWidth {
+    /// How many cells the terminal draws `c` in, if it is printable
+    fn char_width(self, c: char) -> Option<usize> {
+        match self {
+            QRAmbiguousWidth::Narrow => c.width(),
+            QRAmbiguousWidth::Wide => c.width_cjk(),
+        }
+    }
+}
+
+/// `QRInstructions` is the text below terminal QR codes
+///
+/// Only the `qr_text*` methods and the terminal printing methods add it; PNG and SVG output never contain text.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum QRInstructions {
+    /// How to scan the QR code, a warning that it contains the secret key, and troubleshooting tips, in English
+    #[default]
+    English,
+    /// No text, only the QR code itself
+    Hidden,
+    /// Your own lines, e.g. translated ones. BEWARE: keep a warning that the QR code contains the secret key!!
+    Custom(Vec<String>),
+}
+
+/// `QRShading` defines how terminal QR codes turn the sampled image into block characters
+///
+/// QR modules are either dark or light, so the intermediate shades of `Grayscale` are an artifact of sampling pixels that
+/// straddle two modules. `Binary` snaps every character to dark or light, for maximum contrast in both color modes.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "plaintext-serde", derive(Deserialize, Serialize))]
+pub enum QRShading {
+    /// Dark, medium, and light shades (`█`, `▓`, `▒`) depending on how much of each character is dark, using `QRThresholds::default()`
+    #[default]
+    Grayscale,
+    /// Only `█` and space (plus half blocks in mini size): a character is dark if the majority of its pixels are
+    Binary,
+    /// Dark, medium, and light shades with your own thresholds, e.g. tuned for your terminal's width and font
+    Custom(QRThresholds),
+}
+
+/// `QRThresholds` are the percentages of dark pixels above which a character of a terminal QR code is drawn as `█`, `▓`, or `▒`
+///
+/// Characters below all three are drawn as a space. The thresholds are checked from `dark` to `light`, so the first one exceeded wins.
+/// The defaults are 70, 40, and 20; a threshold of 100 is never exceeded, so it turns its shade off.
+///
+/// ```rust
+/// use easy_totp::{QRShading, QRThresholds};
+///
+/// // Two shades only: `█` above 60% dark, `▒` above 30% dark
+/// let shading = QRShading::Custom(QRThresholds { dark: 60, medium: 100, light: 30 });
+/// ```
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "plaintext-serde", derive(Deserialize, Serialize))]
+pub struct QRThresholds {
+    /// Percentage of dark pixels above which a character is drawn as `█`
+    pub dark: u8,
+    /// Percentage of dark pixels above which a character is drawn as `▓`
+    pub medium: u8,
+    /// Percentage of dark pixels above which a character is drawn as `▒`
+    pub light: u8,
+}
+
+#[cfg(feature = "std")]
+impl Default for QRThresholds {
+    fn default() -> Self {
+        QRThresholds {
+            dark: 70,
+            medium: 40,
+            light: 20,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl QRShading {
+    /// The thresholds this shading draws each character with
+    fn thresholds(self) -> QRThresholds {
+        match self {
+            QRShading::Grayscale => QRThresholds::default(),
+            QRShading::Binary => QRThresholds {
+                dark: 50,
+                medium: 100,
+                light: 100,
+            },
+            QRShading::Custom(thresholds) => thresholds,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl QRInstructions {
+    /// The lines to print below the QR code; `kind` names the scheme in the English text (e.g. `"TOTP"`)
+    fn lines(&self, kind: &str) -> Vec<String> {
+        match self {
+            QRInstructions::English => qr_instructions(kind),
+            QRInstructions::Hidden => Vec::new(),
+            QRInstructions::Custom(lines) => lines.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl QREccLevel {
+    /// The equivalent `qrcodegen` error-correction level
+    fn to_qrcodegen(self) -> QrCodeEcc {
+        match self {
+            QREccLevel::Low => QrCodeEcc::Low,
+            QREccLevel::Medium => QrCodeEcc::Medium,
+            QREccLevel::Quartile => QrCodeEcc::Quartile,
+            QREccLevel::High => QrCodeEcc::High,
+        }
+    }
+}
+
+/// Width in modules of the light border around QR codes, as required by the QR code specification
+#[cfg(feature = "std")]
+const DEFAULT_QUIET_ZONE: u8 = 4;
+
+/// Largest width and height of rendered images, in pixels. Keeps the pixel buffer well below what could exhaust memory.
+#[cfg(feature = "std")]
+pub(crate) const MAX_IMAGE_SIZE: u32 = 16_384;
+
+/// Rendering settings shared by every QR code output
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug)]
+struct QrOptions {
+    ecc: QREccLevel,
+    /// Width of the light border around the QR code, in modules
+    quiet_zone: u8,
+    /// Block characters of terminal QR codes
+    shading: QRShading,
+}
+
+#[cfg(feature = "std")]
+impl Default for QrOptions {
+    fn default() -> Self {
+        QrOptions {
+            ecc: QREccLevel::Medium,
+            quiet_zone: DEFAULT_QUIET_ZONE,
+            shading: QRShading::Grayscale,
+        }
+    }
+}
+
+/// `Clock` is a source of the current time, which can be injected with `EasyTotp::with_clock` or passed to `embedded::TotpCore`
+///
+/// Implemented for every `Fn() -> u64`, so a closure is usually all you need.
+///
+/// ```rust
+/// use easy_totp::Clock;
+///
+/// #[derive(Debug)]
+/// struct FixedClock(u64);
+///
+/// impl Clock for FixedClock {
+///     fn now_unix(&self) -> u64 {
+///         self.0
+///     }
+/// }
+/// ```
+pub trait Clock: Send + Sync {
+    /// The current Unix timestamp, in seconds
+    fn now_unix(&self) -> u64;
+}
+
+impl<F: Fn() -> u64 + Send + Sync> Clock for F {
+    fn now_unix(&self) -> u64 {
+        self()
+    }
+}
+
+/// `EasyTotpMetadata` describes an account without its secret key, e.g. for logs and analytics
+///
+/// Returned by `EasyTotp::metadata`. With the `serde` feature, it can be serialized safely.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct EasyTotpMetadata {
+    /// The name of the service, if any
+    pub issuer: Option<String>,
+    /// The account name, e.g. the user's email address
+    pub account_name: String,
+    /// The hash algorithm
+    pub algorithm: Algorithm,
+    /// The number of digits in each token
+    pub digits: usize,
+    /// The time step in seconds
+    pub period: u64,
+}
+
+/// `RngRetryPolicy` defines how often `EasyTotp::new_with_retry` tries the random number generator before giving up
+///
+/// On some locked-down platforms, or early during boot, the operating system's RNG can fail temporarily.
+/// Retrying never falls back to a weaker RNG: once every attempt has failed, the last error is returned as `EasyTotpError::Rng`.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct RngRetryPolicy {
+    /// How many times to try in total, at least 1
+    pub attempts: u32,
+    /// How long to wait between attempts
+    pub delay: Duration,
+}
+
+#[cfg(feature = "std")]
+impl Default for RngRetryPolicy {
+    /// 3 attempts, 100 milliseconds apart
+    fn default() -> Self {
+        RngRetryPolicy {
+            attempts: 3,
+            delay: Duration::from_millis(100),
+        }
+    }
+}
+
+/// `SanitizedLabel` is the issuer and account name encoded by `EasyTotp::to_ascii_otpauth_uri`
+///
+/// Check `dropped` before handing out the URI: if it isn't empty, the label differs from the account's own.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SanitizedLabel {
+    /// The issuer as encoded, or `None` if there was none or none of its characters were kept
+    pub issuer: Option<String>,
+    /// The account name as encoded
+    pub account_name: String,
+    /// Every character that was left out, issuer first, in order of appearance
+    pub dropped: Vec<char>,
+}
+
+/// `TokenInfo` is a TOTP token along with the time step it belongs to
+///
+/// Returned by `EasyTotp::token_info`.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct TokenInfo {
+    /// The token itself
+    pub code: String,
+    /// How many seconds the token remains valid, in the range `1..=period`
+    pub seconds_remaining: u64,
+    /// The RFC 6238 time step counter (`unix_time / period`)
+    ///
+    /// Record the step of each accepted token to reject replays of it within the same step.
+    pub step: u64,
+}
+
+/// `EnrollmentInfo` is everything a user needs to add an account to their authenticator app
+///
+/// Returned by `EasyTotp::enroll`. Its `Display` output is the QR code, followed by the secret key for manual entry and the otpauth URI.
+/// `Debug` output redacts all three, and they are zeroized on drop.
+///
+/// BEWARE: contains secret!!
+#[cfg(feature = "std")]
+#[derive(Clone, Eq, PartialEq)]
+pub struct EnrollmentInfo {
+    /// The otpauth URI, as encoded in the QR code
+    pub uri: String,
+    /// The secret key in base32, for apps that can't scan QR codes
+    pub secret_base32: String,
+    /// The QR code as lines of text, as printed by `EasyTotp::print_qr_to_terminal`
+    pub qr_lines: Vec<String>,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Debug for EnrollmentInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnrollmentInfo")
+            .field("uri", &"<redacted>")
+            .field("secret_base32", &"<redacted>")
+            .field(
+                "qr_lines",
+                &format!("<redacted {} lines>", self.qr_lines.len()),
+            )
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for EnrollmentInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.qr_lines {
+            writeln!(f, "{line}")?;
+        }
+        writeln!(f, "Secret key: {}", self.secret_base32)?;
+        write!(f, "URI: {}", self.uri)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for EnrollmentInfo {
+    fn drop(&mut self) {
+        self.uri.zeroize();
+        self.secret_base32.zeroize();
+        self.qr_lines.zeroize();
+    }
+}
+
 /// `EasyTotp` is a unit-struct to keep track of externally-implemented code.
-#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+///
+/// ## Secret handling
+/// The secret key is overwritten with zeroes (zeroized) when an `EasyTotp` is dropped, and every clone zeroizes its own copy
+/// independently. The cached `TOTP` instance zeroizes its copy too.
+///
+/// This does not cover copies handed out to you, such as the output of `secret_base32`, `to_otpauth_uri`, or the QR code
+/// images, nor stale copies the allocator may have left behind when a buffer was moved or reallocated.
+///
+/// BEWARE: with the `plaintext-serde` feature, `Serialize` writes the secret key in plaintext!! To store an `EasyTotp`, prefer `to_encrypted`.
+#[cfg(feature = "std")]
+#[derive(Clone)]
+#[cfg_attr(feature = "plaintext-serde", derive(Deserialize, Serialize))]
 pub struct EasyTotp {
-    raw_secret: String,
+    raw_secret: Zeroizing<Vec<u8>>,
     issuer: Option<String>,
     account_name: String,
+    algorithm: Algorithm,
+    digits: usize,
+    period: u64,
+    skew: u8,
+    #[cfg_attr(feature = "plaintext-serde", serde(default))]
+    qr_ecc: QREccLevel,
+    #[cfg_attr(feature = "plaintext-serde", serde(default = "default_quiet_zone"))]
+    quiet_zone: u8,
+    #[cfg_attr(feature = "plaintext-serde", serde(default = "default_min_secret_len"))]
+    min_secret_len: usize,
+    /// `https://` URL of an icon for authenticator apps, added to the otpauth URI as `image`
+    #[cfg_attr(feature = "plaintext-serde", serde(default))]
+    icon_url: Option<String>,
+    /// Text below terminal QR codes. Presentation only, so it's neither compared nor saved.
+    #[cfg_attr(feature = "plaintext-serde", serde(skip))]
+    qr_instructions: QRInstructions,
+    /// Block characters of terminal QR codes. Presentation only, like `qr_instructions`.
+    #[cfg_attr(feature = "plaintext-serde", serde(skip))]
+    qr_shading: QRShading,
+    /// Lazily-built `TOTP` instance, reused across calls. Cleared whenever a setting changes.
+    #[cfg_attr(feature = "plaintext-serde", serde(skip))]
+    totp: OnceLock<TOTP>,
+    /// Injected time source; the system clock when `None`
+    #[cfg_attr(feature = "plaintext-serde", serde(skip))]
+    clock: Option<Arc<dyn Clock>>,
+}
+
+#[cfg(feature = "std")]
+impl PartialEq for EasyTotp {
+    fn eq(&self, other: &Self) -> bool {
+        // The cached `TOTP` is derived from the other fields, and neither the clock nor the QR instructions and shading are settings of the credential,
+        // so all four are deliberately ignored
+        self.raw_secret == other.raw_secret
+            && self.issuer == other.issuer
+            && self.account_name == other.account_name
+            && self.algorithm == other.algorithm
+            && self.digits == other.digits
+            && self.period == other.period
+            && self.skew == other.skew
+            && self.qr_ecc == other.qr_ecc
+            && self.quiet_zone == other.quiet_zone
+            && self.min_secret_len == other.min_secret_len
+            && self.icon_url == other.icon_url
+    }
+}
+
+#[cfg(feature = "std")]
+impl Eq for EasyTotp {}
+
+#[cfg(feature = "std")]
+impl fmt::Debug for EasyTotp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EasyTotp")
+            .field("raw_secret", &redacted_secret(&self.raw_secret))
+            .field("issuer", &self.issuer)
+            .field("account_name", &self.account_name)
+            .field("algorithm", &self.algorithm)
+            .field("digits", &self.digits)
+            .field("period", &self.period)
+            .field("skew", &self.skew)
+            .field("qr_ecc", &self.qr_ecc)
+            .field("quiet_zone", &self.quiet_zone)
+            .field("min_secret_len", &self.min_secret_len)
+            .field("icon_url", &self.icon_url)
+            .field("qr_instructions", &self.qr_instructions)
+            .field("qr_shading", &self.qr_shading)
+            .field(
+                "clock",
+                &if self.clock.is_some() {
+                    "custom"
+                } else {
+                    "system"
+                },
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+/// Shows the account and token format, e.g. `EasyTotp(issuer: McCormick, account: test@test-email.com, algorithm: SHA512, digits: 6, period: 30s)`
+///
+/// The secret key is left out entirely, so this is safe to log or show to users.
+#[cfg(feature = "std")]
+impl fmt::Display for EasyTotp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "EasyTotp(issuer: {}, account: {}, algorithm: {}, digits: {}, period: {}s)",
+            self.issuer.as_deref().unwrap_or("none"),
+            self.account_name,
+            self.algorithm_name(),
+            self.digits,
+            self.period,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for EasyTotp {
+    fn default() -> Self {
+        EasyTotp {
+            raw_secret: Zeroizing::new(Vec::new()),
+            issuer: None,
+            account_name: String::new(),
+            algorithm: EasyTotp::DEFAULT_ALGORITHM,
+            digits: EasyTotp::DEFAULT_DIGITS,
+            period: EasyTotp::DEFAULT_PERIOD,
+            skew: EasyTotp::DEFAULT_SKEW,
+            qr_ecc: QREccLevel::Medium,
+            quiet_zone: DEFAULT_QUIET_ZONE,
+            min_secret_len: MIN_SECRET_LEN,
+            icon_url: None,
+            qr_instructions: QRInstructions::English,
+            qr_shading: QRShading::Grayscale,
+            totp: OnceLock::new(),
+            clock: None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<TOTP> for EasyTotp {
+    type Error = EasyTotpError;
+
+    /// Wraps an existing `totp_rs::TOTP`, keeping its secret, issuer, account name, algorithm, digits, period, and skew
+    ///
+    /// ```rust
+    /// use easy_totp::{Algorithm, EasyTotp};
+    /// use totp_rs::TOTP;
+    ///
+    /// let totp = TOTP::new(
+    ///     Algorithm::SHA1,
+    ///     6,
+    ///     1,
+    ///     30,
+    ///     b"12345678901234567890".to_vec(),
+    ///     Some(String::from("McCormick")),
+    ///     String::from("test@test-email.com"),
+    /// )
+    /// .unwrap();
+    ///
+    /// let et = EasyTotp::try_from(totp).unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the `TOTP` has settings `EasyTotp` rejects, e.g. a secret shorter than 128 bits.
+    fn try_from(totp: TOTP) -> Result<Self, Self::Error> {
+        let et = EasyTotp {
+            raw_secret: Zeroizing::new(totp.secret.clone()),
+            issuer: totp.issuer.clone(),
+            account_name: totp.account_name.clone(),
+            algorithm: totp.algorithm,
+            skew: totp.skew,
+            ..EasyTotp::default()
+        }
+        .with_token_format(totp.digits, totp.step)?;
+
+        // Validate the label and the secret, and keep the existing instance as the cache
+        validate_label(et.issuer.as_deref(), &et.account_name)?;
+        if et.raw_secret.len() < MIN_SECRET_LEN {
+            return Err(EasyTotpError::InvalidSetting(format!(
+                "secret key must be at least {MIN_SECRET_LEN} bytes, not {}",
+                et.raw_secret.len()
+            )));
+        }
+        let _ = et.totp.set(totp);
+
+        Ok(et)
+    }
 }
 
+#[cfg(feature = "std")]
 impl EasyTotp {
+    /// The default hash algorithm; see `with_algorithm`
+    pub const DEFAULT_ALGORITHM: Algorithm = Algorithm::SHA512;
+    /// The default number of digits in each token; see `with_digits`
+    pub const DEFAULT_DIGITS: usize = 6;
+    /// The default time step in seconds; see `with_period`
+    pub const DEFAULT_PERIOD: u64 = 30;
+    /// The default verification skew in time steps; see `with_skew`
+    pub const DEFAULT_SKEW: u8 = 1;
+    /// A QR code version (57x57 modules) that phone cameras reliably scan from a screen or print; see `check_qr_density`
+    pub const RECOMMENDED_MAX_QR_VERSION: u8 = 10;
+    /// Punctuation that `to_ascii_otpauth_uri` commonly keeps besides letters and digits, enough for email addresses
+    pub const ASCII_LABEL_PUNCTUATION: &'static str = "@.-_+ ";
+
     /// Creates a new `EasyTotp` instance with a randomly generated secret key
-    /// 
+    ///
+    /// The issuer is the name of your service. With `None`, it is left out of the otpauth URI, and many authenticator apps
+    /// then list the account by its bare account name (e.g. just an email address). Use `EasyTotpBuilder::require_issuer` to enforce one.
+    ///
     /// ## Example
-    /// 
+    ///
     /// ```rust
     /// use easy_totp::EasyTotp;
-    /// 
+    ///
     /// let issuer = Some(String::from("McCormick"));
     /// let account_name = String::from("account_name");
     /// let et = EasyTotp::new(issuer, account_name).unwrap();
     /// ```
     ///
     /// ## Errors
-    /// This function will return an error if the random number generator fails to generate bytes for the secret key.
-    pub fn new(
+    /// This function will return `EasyTotpError::InvalidSetting` if the account name, or the issuer if given, is empty or whitespace-only
+    /// or contains a `:`, and `EasyTotpError::Rng` if the random number generator fails to generate bytes for the secret key.
+    /// To retry it, use `new_with_retry`.
+    pub fn new(issuer: Option<String>, account_name: String) -> Result<Self, EasyTotpError> {
+        Self::new_from_rng(&mut OsRng, issuer, account_name)
+    }
+
+    /// Creates a new `EasyTotp` instance like `new`, but draws the secret key from `rng` instead of `OsRng`
+    ///
+    /// For environments that must use a particular CSPRNG, e.g. a FIPS-validated module, and for tests: a seeded RNG yields
+    /// the same secret key, and so the same tokens, on every run. Any `CryptoRng + RngCore` works, as well as fallible
+    /// RNGs implementing `TryCryptoRng`, whose errors are returned as `EasyTotpError::Rng`.
+    ///
+    /// BEWARE: the secret key is only as unpredictable as `rng`!! It must be a cryptographically secure RNG, seeded from
+    /// a secure source of entropy; the `CryptoRng` marker trait is a promise by its implementer, not a guarantee.
+    /// Fixed seeds are for tests only.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    /// use rand::{SeedableRng, rngs::StdRng};
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et1 = EasyTotp::new_from_rng(&mut StdRng::seed_from_u64(42), issuer.clone(), account_name.clone()).unwrap();
+    /// let et2 = EasyTotp::new_from_rng(&mut StdRng::seed_from_u64(42), issuer, account_name).unwrap();
+    ///
+    /// assert_eq!(et1.secret_base32(), et2.secret_base32());
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return `EasyTotpError::InvalidSetting` if the account name, or the issuer if given, is empty or whitespace-only
+    /// or contains a `:`, and `EasyTotpError::Rng` if `rng` fails to generate bytes for the secret key.
+    pub fn new_from_rng<R>(
+        rng: &mut R,
         issuer: Option<String>,
         account_name: String,
-    ) -> Result<Self, <OsRng as TryRngCore>::Error> {
-        // Use OsRng to generate a random secret key
-        let mut secret_bytes = [0u8; 20];
-        OsRng.try_fill_bytes(&mut secret_bytes)?;
-        let raw_secret = String::from_utf8_lossy(&secret_bytes).to_string();
-
-        let issuer = match issuer {
-            Some(iss) => Some(iss.to_string()),
-            None => None,
-        };
+    ) -> Result<Self, EasyTotpError>
+    where
+        R: TryCryptoRng + ?Sized,
+        R::Error: Error + Send + Sync + 'static,
+    {
+        validate_label(issuer.as_deref(), &account_name)?;
+        validate_names_not_blank(issuer.as_deref(), &account_name)?;
+
         Ok(EasyTotp {
-            raw_secret,
+            raw_secret: random_secret_from(rng, DEFAULT_SECRET_LEN)?,
             issuer,
-            account_name: account_name.to_string(),
+            account_name,
+            ..Default::default()
         })
     }
 
-    /// Creates a new TOTP instance
-    fn new_totp(&self) -> Result<TOTP, EasyTotpError> {
-        let secret;
-        let result_secret = Secret::Raw(self.raw_secret.as_bytes().to_vec()).to_bytes();
-
-        if let Ok(okay_secret) = result_secret {
-            secret = okay_secret;
-        } else {
-            return Err(EasyTotpError::new("Failed to parse secret key"));
-        }
+    /// Creates a new `EasyTotp` instance like `new`, but retries the random number generator according to `policy` if it fails
+    ///
+    /// For applications that would rather wait briefly than fail at startup when the operating system's RNG is temporarily unavailable.
+    /// `new` makes a single attempt.
+    ///
+    /// ```rust
+    /// use easy_totp::{EasyTotp, RngRetryPolicy};
+    /// use std::time::Duration;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let policy = RngRetryPolicy { attempts: 5, delay: Duration::from_millis(200) };
+    /// let et = EasyTotp::new_with_retry(issuer, account_name, policy).unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return `EasyTotpError::InvalidSetting` if the account name, or the issuer if given, is empty or whitespace-only
+    /// or contains a `:`, or if `policy.attempts` is zero, and `EasyTotpError::Rng` with the last attempt's error if every attempt fails.
+    pub fn new_with_retry(
+        issuer: Option<String>,
+        account_name: String,
+        policy: RngRetryPolicy,
+    ) -> Result<Self, EasyTotpError> {
+        Self::new_from_rng_with_retry(&mut OsRng, issuer, account_name, policy)
+    }
 
-        let result = TOTP::new(
-            Algorithm::SHA512,
-            6,
-            1,
-            30,
-            secret,
-            self.issuer.clone(),
-            self.account_name.clone(),
-        );
+    /// Creates a new `EasyTotp` instance like `new_from_rng`, but retries `rng` according to `policy` if it fails
+    ///
+    /// See `new_with_retry`. BEWARE: the secret key is only as unpredictable as `rng`!!
+    ///
+    /// ## Errors
+    /// This function will return `EasyTotpError::InvalidSetting` if the account name, or the issuer if given, is empty or whitespace-only
+    /// or contains a `:`, or if `policy.attempts` is zero, and `EasyTotpError::Rng` with the last attempt's error if every attempt fails.
+    pub fn new_from_rng_with_retry<R>(
+        rng: &mut R,
+        issuer: Option<String>,
+        account_name: String,
+        policy: RngRetryPolicy,
+    ) -> Result<Self, EasyTotpError>
+    where
+        R: TryCryptoRng + ?Sized,
+        R::Error: Error + Send + Sync + 'static,
+    {
+        validate_label(issuer.as_deref(), &account_name)?;
+        validate_names_not_blank(issuer.as_deref(), &account_name)?;
 
-        if let Ok(okay_result) = result {
-            Ok(okay_result)
-        } else {
-            Err(EasyTotpError::new("Error creating new TOTP instance"))
+        if policy.attempts == 0 {
+            return Err(EasyTotpError::InvalidSetting(String::from(
+                "RNG retry policy must allow at least 1 attempt",
+            )));
         }
-    }
 
-    fn create_qr(&self) -> Result<String, EasyTotpError> {
-        let result = Self::new_totp(self)?.get_qr_base64();
+        let mut attempt = 1;
+        let raw_secret = loop {
+            match random_secret_from(rng, DEFAULT_SECRET_LEN) {
+                Ok(secret) => break secret,
+                Err(e) if attempt == policy.attempts => return Err(e),
+                Err(_) => {
+                    attempt += 1;
+                    std::thread::sleep(policy.delay);
+                }
+            }
+        };
 
-        if let Ok(okay_result) = result {
-            Ok(okay_result)
-        } else {
-            Err(EasyTotpError::new("Error creating QR code data"))
-        }
+        Ok(EasyTotp {
+            raw_secret,
+            issuer,
+            account_name,
+            ..Default::default()
+        })
     }
 
-    /// Generates QR code text for terminal display, but does not actually print it.
+    /// Creates a new `EasyTotp` instance like `new`, then checks that the secret key survives being exported and imported again
     ///
-    /// BEWARE: output contains secret!!
+    /// The secret is encoded to base32 and decoded back, and read back from the otpauth URI, and both must match the generated bytes.
+    /// A safety net against secrets being corrupted on the way to the authenticator app, at the cost of some extra work, so `new` skips it.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new_checked(issuer, account_name).unwrap();
+    /// ```
     ///
     /// ## Errors
-    /// This function will return an error if the QR code generation or image processing fails.
-    #[allow(clippy::cast_precision_loss)]
-    pub fn qr_text(
-        &self,
-        size: TerminalQRSize,
-        mode: QRColorMode,
-    ) -> Result<Vec<String>, Box<dyn Error>> {
-        let mut lines = Vec::new();
-        let decoded_data = general_purpose::STANDARD.decode(Self::create_qr(self)?)?;
-
-        let img = image::load_from_memory(&decoded_data)?.to_luma8();
-
-        let width = img.width();
-        let height = img.height();
-
-        // Determine scaling factor to fit terminal
-        let terminal_width = 100; // Typical terminal width in characters
-        let scale_x = width / terminal_width;
-        let scale_y = scale_x * 2; // Height is doubled for character aspect ratio
-
-        for y in (0..height).step_by(scale_y as usize) {
-            let mut line = String::new();
-            for x in (0..width).step_by(scale_x as usize) {
-                // Sample the block of pixels and determine overall darkness
-                let block_darkness = (0..scale_x)
-                    .flat_map(|dx| {
-                        (0..scale_y).map({
-                            let img_val = img.clone();
-                            move |dy| {
-                                let px = (x + dx).min(width - 1);
-                                let py = (y + dy).min(height - 1);
-                                img_val.get_pixel(px, py)[0]
-                            }
-                        })
-                    })
-                    .filter(|&p| p < 128)
-                    .count();
-
-                let total_pixels = (scale_x * scale_y) as usize;
-                let symbol = match block_darkness as f32 / total_pixels as f32 {
-                    d if d > 0.7 => '█', // Very dark
-                    d if d > 0.4 => '▓', // Medium-dark
-                    d if d > 0.2 => '▒', // Light
-                    _ => ' ',            // Very light
-                };
+    /// This function will return an error if the random number generator fails, the issuer or account name are blank or contain a `:`,
+    /// or the secret key does not survive the round trip.
+    pub fn new_checked(
+        issuer: Option<String>,
+        account_name: String,
+    ) -> Result<Self, EasyTotpError> {
+        let et = Self::new(issuer, account_name)?;
+        et.check_secret_round_trip()?;
 
-                line.push(symbol);
-            }
-            lines.push(line);
-        }
-        lines.push(String::from(
-            "Scan the above QR code with your authenticator app to set up TOTP.",
-        ));
-        lines.push(String::from(
-            "BEWARE: this QR code contains your secret key! Handle with care.",
-        ));
-        lines.push(String::from("Useful tips: if scanning fails, try inverting the QR code colors by adjusting your terminal's background color or "));
-        lines.push(String::from("using your mouse to select the entire QR code area. Also, ensure your terminal zoom is set to a level that allows "));
-        lines.push(String::from(
-            "the QR code to be completely visible onscreen.",
-        ));
+        Ok(et)
+    }
 
-        stdout().flush()?;
-
-        match mode {
-            QRColorMode::Direct => {}
-            QRColorMode::Inverted => {
-                for line in &mut lines {
-                    // Skip lines that contain alphanumeric text
-                    if line.chars().any(|c| {
-                        c.is_alphanumeric() || c == ':' || c == '.' || c == '@' || c == '!'
-                    }) {
-                        continue;
-                    }
+    /// Checks that the secret key survives encoding to base32 and the otpauth URI
+    fn check_secret_round_trip(&self) -> Result<(), EasyTotpError> {
+        let from_base32 = decode_base32_secret(&self.secret_base32())?;
+        let from_uri = Self::from_otpauth_uri(&self.to_otpauth_uri()?)?;
 
-                    *line = line
-                        .chars()
-                        .map(|c| match c {
-                            '█' => ' ',
-                            '▓' => '░',
-                            '▒' => '▓',
-                            ' ' => '█',
-                            _ => c,
-                        })
-                        .collect();
-                }
-            }
+        if !constant_time_eq(&from_base32, &self.raw_secret)
+            || !constant_time_eq(&from_uri.raw_secret, &self.raw_secret)
+        {
+            return Err(EasyTotpError::SecretParse(String::from(
+                "secret key changed when encoded and decoded again",
+            )));
         }
 
-        match size {
-            TerminalQRSize::Full => Ok(lines),
-            TerminalQRSize::Mini => {
-                let mut mini_lines = Vec::new();
-
-                for line in lines.chunks(2) {
-                    let mut mini_line = String::new();
-                    for (c1, c2) in line[0]
-                        .chars()
-                        .zip(line.get(1).unwrap_or(&String::new()).chars())
-                    {
-                        let mini_char = match (c1, c2) {
-                            ('█' | '▓' | '▒', ' ') => '▀',
-                            (' ', '█' | '▓' | '▒') => '▄',
-                            ('█', '█') => '█',
-                            ('▓', '▓') => '▓',
-                            ('▒', '▒') => '▒',
-                            _ => ' ',
-                        };
-                        mini_line.push(mini_char);
-                    }
-                    mini_lines.push(mini_line);
-                }
-
-                Ok(mini_lines)
-            }
-        }
+        Ok(())
     }
 
-    /// Creates a new PNG with a QR code
+    /// Checks that the current token passes verification, and that the secret key survives being exported and imported again
     ///
-    /// BEWARE: PNG image contains secret!!
+    /// Meant for health checks, e.g. at startup: it catches misconfigured or corrupted credentials before the first user
+    /// tries to log in with them. The token is generated and verified at the same instant, so clock drift can't make it fail.
     ///
-    /// ## Creating a QR code for TOTP setup
     /// ```rust
     /// use easy_totp::EasyTotp;
     ///
@@ -334,287 +1004,5849 @@ impl EasyTotp {
     /// let account_name = String::from("test@test-email.com");
     /// let et = EasyTotp::new(issuer, account_name).unwrap();
     ///
-    /// let my_qr_code = et.create_qr_png().unwrap();
+    /// et.self_check().unwrap();
     /// ```
     ///
     /// ## Errors
-    /// This function will return an error if the QR code generation or image processing fails.
-    pub fn create_qr_png(&self) -> Result<Vec<u8>, Box<dyn Error>> {
-        // Decode the base64 string
-        let decoded_data = general_purpose::STANDARD.decode(Self::create_qr(self)?)?;
+    /// This function will return `EasyTotpError::SelfCheck` if the token is rejected, or another error if the TOTP instance cannot be created,
+    /// the system time is unavailable, or the secret key does not survive the round trip.
+    pub fn self_check(&self) -> Result<(), EasyTotpError> {
+        self.check_secret_round_trip()?;
 
-        // Create a dynamic image from the decoded data
-        let image = image::load_from_memory(&decoded_data)?;
-
-        // Write the image to a buffer as a PNG
-        let mut buffer = Vec::new();
-        let mut cursor = Cursor::new(&mut buffer);
-        image.write_to(&mut cursor, image::ImageFormat::Png)?;
+        let now = self.now()?;
+        let token = Zeroizing::new(self.totp()?.generate(now));
+        if !self.verify_token_at(&token, now)? {
+            return Err(EasyTotpError::SelfCheck(format!(
+                "the token generated at Unix time {now} was rejected at the same time"
+            )));
+        }
 
-        Ok(buffer)
+        Ok(())
     }
 
-    /// Print the QR code to the terminal
+    /// Creates a new `EasyTotp` instance with a randomly generated secret key of `bytes` bytes
     ///
-    /// BEWARE: terminal will display secret!!
+    /// `new` uses 20 bytes (160 bits). Longer secrets suit higher-security deployments, up to a point: HMAC hashes any key
+    /// longer than the algorithm's block size (64 bytes for SHA1 and SHA256, 128 bytes for SHA512) down to its output size first.
+    /// RFC 6238 pairs each algorithm with a secret of its output size: 20 bytes for SHA1, 32 for SHA256, and 64 for SHA512.
     ///
     /// ```rust
-    /// use easy_totp::{EasyTotp, QRColorMode};
+    /// use easy_totp::EasyTotp;
+    ///
     /// let issuer = Some(String::from("McCormick"));
     /// let account_name = String::from("test@test-email.com");
-    /// let et = EasyTotp::new(issuer, account_name).unwrap();
-    /// et.print_qr_to_teminal(QRColorMode::Inverted).unwrap();
+    /// let et = EasyTotp::new_with_secret_length(issuer, account_name, 64).unwrap();
     /// ```
     ///
-    /// That will print out a QR code in the terminal that you can potentially scan with your authenticator app. Your mileage may vary.
-    ///
-    /// ### Example authenticator apps and whether they can scan from terminal output
-    /// - ✔️ Aegis Authenticator (Android)
-    /// - ✔️ Bitwarden Authenticator (Android)
-    /// - ✔️ Proton Authenticator (Android)
-    /// - ✔️ Google Authenticator (Android)
+    /// ## Errors
+    /// This function will return an error if `bytes` is less than 16 (the RFC 4226 minimum), if the issuer or account name are blank
+    /// or contain a `:`, or if the random number generator fails.
+    pub fn new_with_secret_length(
+        issuer: Option<String>,
+        account_name: String,
+        bytes: usize,
+    ) -> Result<Self, EasyTotpError> {
+        if bytes < MIN_SECRET_LEN {
+            return Err(EasyTotpError::InvalidSetting(format!(
+                "secret key must be at least {MIN_SECRET_LEN} bytes, not {bytes}"
+            )));
+        }
+
+        validate_label(issuer.as_deref(), &account_name)?;
+        validate_names_not_blank(issuer.as_deref(), &account_name)?;
+
+        Ok(EasyTotp {
+            raw_secret: random_secret(bytes)?,
+            issuer,
+            account_name,
+            ..Default::default()
+        })
+    }
+
+    /// Returns a builder for configuring every setting of a new `EasyTotp` instance at once
     ///
-    /// ```text
-    /// ██████████████████████████████████████████████████████████████████████████████████████████████████████████████████
-    /// ██████████████████████████████████████████████████████████████████████████████████████████████████████████████████
-    /// ██████████████████████████████████████████████████████████████████████████████████████████████████████████████████
-    /// ██████████████████████████████████████████████████████████████████████████████████████████████████████████████████
-    /// ████████              ████        ██      ██      ██████            ████  ██  ████  ████  ██              ████████
-    /// ████████  ██████████  ████████        ██  ██      ██  ██  ██████  ██        ██  ██        ██  ██████████  ████████
-    /// ████████  ██      ██  ██    ██████████████    ██  ██████  ████  ████████  ██  ████████    ██  ██      ██  ████████
-    /// ████████  ██      ██  ██        ██        ████████      ██    ████      ████████    ██  ████  ██      ██  ████████
-    /// ████████  ██      ██  ██  ██████    ██                        ██      ██  ████      ████████  ██      ██  ████████
-    /// ████████  ██████████  ██  ████  ██  ████████████  ██  ██████    ██████████    ████    ██████  ██████████  ████████
-    /// ████████              ██  ██  ██  ██  ██  ██  ██  ██  ██  ██  ██  ██  ██  ██  ██  ██  ██  ██              ████████
-    /// ████████████████████████    ██    ██  ████  ██        ██████  ██████    ████    ████████  ████████████████████████
-    /// ████████  ██          ████  ████  ██████    ████              ████████  ████████          ██          ████████████
-    /// ████████  ████      ██    ██  ████  ████        ██  ██████  ████        ████████    ██████          ██████████████
-    /// ████████████████████  ██    ██          ██████  ██      ██      ████  ████  ██    ██  ██    ██    ██      ████████
-    /// ██████████    ██    ████        ██████  ██      ██  ██  ██  ████  ██████  ██████    ██  ██    ████████    ████████
-    /// ████████  ██████      ████  ████  ██  ████      ██    ██      ████      ██        ████  ████    ██████  ██████████
-    /// ██████████  ██████  ████      ████      ██    ████  ██      ██████      ██████  ██  ██████    ██  ██    ██████████
-    /// ████████████████  ██      ██████    ████        ████████  ████████  ██          ██████  ██████████████    ████████
-    /// ████████████████    ██  ████    ██      ██    ████  ████  ██  ██████    ██  ██    ██  ██  ██  ██  ████  ██████████
-    /// ██████████████  ████  ████████████████    ████    ████    ████  ██    ██████████      ██    ██  ██  ██  ██████████
-    /// ████████  ██  ██  ██████  ██████████████    ██  ████████    ████████    ██  ██    ████████    ██  ████  ██████████
-    /// ████████████████        ████        ██████  ██      ██    ████  ██    ██████████  ██████  ████      ████  ████████
-    /// ██████████    ██  ████  ██  ██  ██████    ██  ████████  ████████████        ████    ████          ████████████████
-    /// ████████      ██████  ████        ██████  ██  ████    ██    ██████  ██  ████      ████    ████  ████  ██  ████████
-    /// ████████████  ██    ██    ██  ██████  ████      ████  ██  ████████        ██████    ██  ██      ██  ██  ██████████
-    /// ████████████              ██        ██      ██    ██          ██  ████      ████████  ██          ██████  ████████
-    /// ████████████  ██  ██████    ██    ████  ████    ████  ██████  ████████████      ██  ████  ██████  ████    ████████
-    /// ████████    ██    ██  ██    ██    ██  ██████  ██      ██  ██  ████  ██  ████        ████  ██  ██      ████████████
-    /// ████████████      ██████  ██  ████  ██    ██████████  ██████          ██  ████      ████  ██████    ██████████████
-    /// ██████████                ████        ██    ██████            ██        ██  ██    ██              ██      ████████
-    /// ████████    ██████  ██  ██      ████  ██  ██    ██          ██        ██    ██████    ██  ██    ██████    ████████
-    /// ████████████  ██            ██████  ██      ████████        ████████    ████████          ██  ██    ██    ████████
-    /// ██████████  ██    ████        ██  ██        ██      ████  ████  ██    ██  ██████    ██  ██████  ████  ████████████
-    /// ████████      ██  ██        ██    ██  ██  ██████  ██      ████████████████    ██  ████████        ████    ████████
-    /// ████████  ██        ████████  ████████  ██████    ██████  ██    ██  ████████  ██          ████████████    ████████
-    /// ████████  ████  ████  ████  ████  ██      ████    ██    ██  ████  ████          ████    ██████          ██████████
-    /// ████████  ██      ████  ██████    ██  ██  ████  ████████            ████  ██  ████    ██████  ████  ██  ██████████
-    /// ████████████  ██      ██      ████    ████████            ██████  ██        ██  ████████  ██  ██    ██    ████████
-    /// ████████  ██    ██  ██            ████  ██  ██████  ██        ██  ██████    ██        ████  ██  ██████    ████████
-    /// ████████  ██      ██  ████  ████    ██  ██      ██        ████  ██  ██  ████                ████          ████████
-    /// ████████  ██        ██      ██      ██████  ██  ██          ████████      ████  ██  ██      ██████        ████████
-    /// ██████████  ██████      ████████  ██    ██  ████  ██  ██  ██████    ██  ██    ██      ████    ████████    ████████
-    /// ██████████      ████████  ██    ██  ██        ██                ████    ████  ████████████████    ████  ██████████
-    /// ████████      ██████    ██████  ████████    ██                ████  ██  ██                            ████████████
-    /// ████████████████████████  ██████  ████          ████  ██████  ██          ████████  ██    ██████  ██  ████████████
-    /// ████████              ████        ██  ████  ██        ██  ██      ██  ██    ████      ██  ██  ██  ████    ████████
-    /// ████████  ██████████  ██    ██████████████████████    ██████  ██  ████    ██    ████      ██████  ████    ████████
-    /// ████████  ██      ██  ██      ████  ██  ██████  ████          ██████    ██  ██    ██  ██          ████  ██████████
-    /// ████████  ██      ██  ██  ████  ████████    ██████  ██  ██                ██████    ██              ██  ██████████
-    /// ████████  ██      ██  ██    ██    ██  ████      ██    ██  ██      ████              ██████  ██  ████      ████████
-    /// ████████  ██████████  ████                  ██████████        ██████    ██  ██    ████████      ████████  ████████
-    /// ████████              ██  ████    ██  ██  ██      ████    ████  ██    ██████████    ████      ████  ██    ████████
-    /// ██████████████████████████████████████████████████████████████████████████████████████████████████████████████████
-    /// ██████████████████████████████████████████████████████████████████████████████████████████████████████████████████
-    /// ██████████████████████████████████████████████████████████████████████████████████████████████████████████████████
-    /// ██████████████████████████████████████████████████████████████████████████████████████████████████████████████████
-    /// Scan the above QR code with your authenticator app to set up TOTP.
-    /// BEWARE: this QR code contains your secret key! Handle with care.
-    /// Useful tips: if scanning fails, try inverting the QR code colors by adjusting your terminal's background color or
-    /// using your mouse to select the entire QR code area. Also, ensure your terminal zoom is set to a level that allows
-    /// the QR code to be completely visible onscreen.
-    ///                                               
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let et = EasyTotp::builder()
+    ///     .issuer("McCormick")
+    ///     .account_name("test@test-email.com")
+    ///     .digits(8)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    #[must_use]
+    pub fn builder() -> EasyTotpBuilder {
+        EasyTotpBuilder::default()
+    }
+
+    /// Creates a new `EasyTotp` instance from an existing base32-encoded secret key
+    ///
+    /// Useful when migrating accounts from another system. The secret must only contain the characters `A`–`Z` and `2`–`7`,
+    /// optionally followed by `=` padding. Letters may be lowercase and whitespace is ignored, so a secret copied from an
+    /// authenticator app's manual entry screen, such as `jbsw y3dp ehpk 3pxp`, works as is.
+    ///
+    /// BEWARE: handle secrets with caution!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::from_base32_secret("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ", issuer, account_name).unwrap();
     /// ```
+    ///
     /// ## Errors
-    /// This function will return an error if the QR code generation or terminal rendering fails.
-    pub fn print_qr_to_teminal(&self, user_mode: QRColorMode) -> Result<(), Box<dyn Error>> {
-        match user_mode {
-            QRColorMode::Direct => Self::render_qr_terminal_full_direct(self),
-            QRColorMode::Inverted => Self::render_qr_terminal_full_inverted(self),
+    /// This function will return an error if the secret is empty or is not valid base32, or if the issuer or account name contain a `:`.
+    pub fn from_base32_secret(
+        secret: &str,
+        issuer: Option<String>,
+        account_name: String,
+    ) -> Result<Self, EasyTotpError> {
+        validate_label(issuer.as_deref(), &account_name)?;
+
+        Ok(EasyTotp {
+            raw_secret: decode_base32_secret(secret)?,
+            issuer,
+            account_name,
+            ..Default::default()
+        })
+    }
+
+    /// Creates a new `EasyTotp` instance from an `otpauth://totp/...` provisioning URI
+    ///
+    /// The secret, issuer, account name, algorithm, digits, and period are all read from the URI.
+    /// Unknown parameters are ignored, but a parameter given twice is rejected, since apps disagree on which one wins.
+    ///
+    /// BEWARE: URI contains secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let uri = "otpauth://totp/McCormick:test%40test-email.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=McCormick";
+    /// let et = EasyTotp::from_otpauth_uri(uri).unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the URI is malformed or longer than a QR code can hold, is an `otpauth://hotp/...` URI,
+    /// repeats a parameter, contains invalid parameters or percent-encoding that isn't UTF-8, or has an empty account name
+    /// or a name (or label path segment) of `.` or `..`.
+    pub fn from_otpauth_uri(uri: &str) -> Result<Self, EasyTotpError> {
+        if uri.starts_with("otpauth://hotp/") {
+            return Err(EasyTotpError::InvalidUri(String::from(
+                "HOTP URIs are not supported here, use EasyHotp instead",
+            )));
+        }
+
+        if !uri.starts_with("otpauth://totp/") && !uri.starts_with("otpauth://steam/") {
+            return Err(EasyTotpError::InvalidUri(String::from(
+                "URI must start with otpauth://totp/ or otpauth://steam/",
+            )));
+        }
+
+        validate_uri_params(uri)?;
+
+        // URL parsing silently drops `.` and `..` segments of the label, even percent-encoded, which would change the account name
+        let path = uri.split_once('?').map_or(uri, |(path, _)| path);
+        let label = path.split_once("//").map_or(path, |(_, rest)| rest);
+        if label.split('/').skip(1).any(|segment| {
+            urlencoding::decode(segment).is_ok_and(|segment| is_dot_segment(&segment))
+        }) {
+            return Err(EasyTotpError::InvalidUri(String::from(
+                "label must not contain '.' or '..' path segments",
+            )));
+        }
+
+        // `TOTP::from_url` rejects the 5 characters of Steam Guard tokens, so those are validated below instead
+        let steam = uri.starts_with("otpauth://steam/");
+        let result = if steam {
+            TOTP::from_url_unchecked(uri)
+        } else {
+            TOTP::from_url(uri)
+        };
+        let totp = match result {
+            Ok(totp) => totp,
+            Err(e) => return Err(EasyTotpError::TotpConstruction(e)),
+        };
+
+        if totp.account_name.is_empty()
+            || is_dot_segment(&totp.account_name)
+            || totp.issuer.as_deref().is_some_and(is_dot_segment)
+        {
+            return Err(EasyTotpError::InvalidUri(String::from(
+                "label must have an account name other than '.' or '..'",
+            )));
+        }
+
+        let et = EasyTotp {
+            raw_secret: Zeroizing::new(totp.secret.clone()),
+            issuer: totp.issuer.clone(),
+            account_name: totp.account_name.clone(),
+            algorithm: totp.algorithm,
+            icon_url: icon_url_param(uri),
+            ..Default::default()
+        }
+        .with_token_format(totp.digits, totp.step)?;
+
+        if steam {
+            et.totp()?;
+        }
+
+        Ok(et)
+    }
+
+    /// Creates a new `EasyTotp` instance from user input that is either an `otpauth://` URI or a bare base32 secret key
+    ///
+    /// One entry point for a "paste your secret" field, like password managers offer. Input starting with `otpauth://` is parsed
+    /// as in `from_otpauth_uri`; anything else as in `from_base32_secret`, without an issuer and with `default_account` as the
+    /// account name. Surrounding whitespace is ignored.
+    ///
+    /// BEWARE: handle secrets with caution!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let uri = "otpauth://totp/McCormick:test%40test-email.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=McCormick";
+    /// let et = EasyTotp::parse(uri, "fallback@test-email.com").unwrap();
+    /// assert_eq!(et.metadata().account_name, "test@test-email.com");
+    ///
+    /// let et = EasyTotp::parse("gezd gnbv gy3t qojq gezd gnbv gy3t qojq", "test@test-email.com").unwrap();
+    /// assert_eq!(et.metadata().account_name, "test@test-email.com");
+    /// assert_eq!(et.metadata().issuer, None);
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return the error of `from_otpauth_uri` for URIs, and of `from_base32_secret` for anything else,
+    /// e.g. if the input is neither a valid URI nor valid base32.
+    pub fn parse(input: &str, default_account: &str) -> Result<Self, EasyTotpError> {
+        let input = input.trim();
+        if input.starts_with("otpauth://") {
+            EasyTotp::from_otpauth_uri(input)
+        } else {
+            EasyTotp::from_base32_secret(input, None, String::from(default_account))
+        }
+    }
+
+    /// Creates a new Steam Guard `EasyTotp` instance from a base64-encoded Steam `shared_secret`
+    ///
+    /// Steam Guard tokens are 5 characters from the alphabet `23456789BCDFGHJKMNPQRTVWXY` instead of digits, with a 30 second period.
+    /// The issuer is always `Steam`. For a base32-encoded secret, use `from_base32_secret` and `with_algorithm(Algorithm::Steam)` instead.
+    ///
+    /// BEWARE: handle secrets with caution!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::from_steam_shared_secret("c3VwZXJkdXBlcnNlY3JldA==", account_name).unwrap();
+    ///
+    /// assert_eq!(et.generate_token_at(3_000_030).unwrap(), "YRGQJ");
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the secret is not valid base64 or is shorter than 128 bits, or if the account name contains a `:`.
+    pub fn from_steam_shared_secret(
+        shared_secret: &str,
+        account_name: String,
+    ) -> Result<Self, EasyTotpError> {
+        let raw_secret = match general_purpose::STANDARD.decode(shared_secret.trim()) {
+            Ok(secret) => Zeroizing::new(secret),
+            Err(e) => return Err(EasyTotpError::SecretParse(e.to_string())),
+        };
+
+        let et = EasyTotp {
+            raw_secret,
+            issuer: Some(String::from(STEAM_ISSUER)),
+            account_name,
+            ..Default::default()
+        }
+        .with_algorithm(Algorithm::Steam);
+
+        // Building the `TOTP` instance checks the secret length and the account name
+        et.totp()?;
+
+        Ok(et)
+    }
+
+    /// Returns the secret key as an unpadded base32 string, for manual entry into an authenticator app or password manager
+    ///
+    /// The output is exactly the secret embedded in the QR code, so typing it in manually and scanning the QR code yield the same credential.
+    ///
+    /// BEWARE: output contains secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::from_base32_secret("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ", issuer, account_name).unwrap();
+    ///
+    /// assert_eq!(et.secret_base32(), "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+    /// ```
+    #[must_use]
+    pub fn secret_base32(&self) -> String {
+        match &Secret::Raw(self.raw_secret.to_vec()).to_encoded() {
+            Secret::Encoded(encoded) => encoded.clone(),
+            Secret::Raw(_) => unreachable!("Secret::to_encoded always returns Secret::Encoded"),
+        }
+    }
+
+    /// Formats every setting needed to add the account by hand into a printable block, for when the QR code won't scan
+    ///
+    /// Lists the issuer, account name, secret key, algorithm, digits, and period, one per line. The secret key is split into
+    /// groups of 4 characters for easier transcription; authenticator apps ignore the spaces.
+    ///
+    /// BEWARE: output contains secret!! The block starts with a warning to that effect, so keep it in anything you print or display.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::from_base32_secret("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ", issuer, account_name).unwrap();
+    ///
+    /// let details = et.manual_entry_details();
+    /// assert!(details.contains("Secret key: GEZD GNBV GY3T QOJQ GEZD GNBV GY3T QOJQ"));
+    /// ```
+    #[must_use]
+    pub fn manual_entry_details(&self) -> String {
+        let secret = Zeroizing::new(self.secret_base32());
+        let grouped_secret = Zeroizing::new(
+            secret
+                .as_bytes()
+                .chunks(4)
+                .map(|chunk| String::from_utf8_lossy(chunk))
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+        format!(
+            "BEWARE: this contains your secret key!! Anyone who sees it can generate your codes, so never share it.\n\
+             Issuer:     {}\n\
+             Account:    {}\n\
+             Secret key: {}\n\
+             Type:       Time-based (TOTP)\n\
+             Algorithm:  {}\n\
+             Digits:     {}\n\
+             Period:     {} seconds",
+            self.issuer.as_deref().unwrap_or("(none)"),
+            self.account_name,
+            *grouped_secret,
+            self.algorithm_name(),
+            self.digits,
+            self.period,
+        )
+    }
+
+    /// Replaces the secret key with fresh random bytes, keeping the issuer, account name, and every other setting
+    ///
+    /// For rotating credentials, e.g. after a suspected compromise. The new secret has the same length as the old one
+    /// (or the minimum secret length, if that is longer), and the old one is zeroized.
+    ///
+    /// BEWARE: every previously issued QR code, otpauth URI, and token becomes invalid!! Users must enroll again.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let mut et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let old_secret = et.secret_base32();
+    /// et.regenerate_secret().unwrap();
+    /// assert_ne!(et.secret_base32(), old_secret);
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return `EasyTotpError::Rng` if the random number generator fails, in which case the old secret is kept.
+    pub fn regenerate_secret(&mut self) -> Result<(), EasyTotpError> {
+        self.raw_secret = random_secret(self.raw_secret.len().max(self.min_secret_len))?;
+        self.totp.take();
+
+        Ok(())
+    }
+
+    /// Returns the size of the secret key in bits, e.g. to check it against a security policy
+    ///
+    /// This is the secret's entropy if it was randomly generated, as by `new` (160 bits). It is only an upper bound
+    /// for imported secrets, which may have been generated poorly.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// assert!(et.secret_entropy_bits() >= 160);
+    /// ```
+    #[must_use]
+    pub fn secret_entropy_bits(&self) -> usize {
+        self.raw_secret.len() * 8
+    }
+
+    /// Returns how far off the user's clock may be, in seconds, for their tokens to be accepted: `period * (skew + 1)`
+    ///
+    /// For onboarding UIs, e.g. "your codes work within ±60 seconds of the correct time". This is an upper bound: whether an
+    /// offset close to it works depends on where in the time step both clocks are. An offset of up to `period * skew` always works.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// assert_eq!(et.clock_skew_tolerance_seconds(), 60);
+    /// assert_eq!(et.with_skew(0).clock_skew_tolerance_seconds(), 30);
+    /// ```
+    #[must_use]
+    pub fn clock_skew_tolerance_seconds(&self) -> u64 {
+        self.period.saturating_mul(u64::from(self.skew) + 1)
+    }
+
+    /// Returns the issuer, account name, and token format, without the secret key
+    ///
+    /// Unlike the `EasyTotp` itself, the result is safe to move into log contexts or telemetry: there is no secret in it to leak,
+    /// redacted or otherwise.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let metadata = et.metadata();
+    /// assert_eq!(metadata.account_name, "test@test-email.com");
+    /// assert_eq!(metadata.digits, 6);
+    /// ```
+    #[must_use]
+    pub fn metadata(&self) -> EasyTotpMetadata {
+        EasyTotpMetadata {
+            issuer: self.issuer.clone(),
+            account_name: self.account_name.clone(),
+            algorithm: self.algorithm,
+            digits: self.digits,
+            period: self.period,
+        }
+    }
+
+    /// Sets the hash algorithm used for token generation, verification, and the otpauth URI
+    ///
+    /// Defaults to `Algorithm::SHA512` (`EasyTotp::DEFAULT_ALGORITHM`). Many authenticator apps (e.g. Google Authenticator) only support `Algorithm::SHA1`.
+    ///
+    /// `Algorithm::Steam` switches to Steam Guard tokens (see `from_steam_shared_secret`), which also sets the fixed
+    /// Steam format of 5 characters and a 30 second period. Switching back to another algorithm restores the default format.
+    ///
+    /// ```rust
+    /// use easy_totp::{Algorithm, EasyTotp};
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name)
+    ///     .unwrap()
+    ///     .with_algorithm(Algorithm::SHA1);
+    /// ```
+    #[must_use]
+    pub fn with_algorithm(mut self, algorithm: Algorithm) -> Self {
+        if algorithm == Algorithm::Steam {
+            self.digits = STEAM_DIGITS;
+            self.period = STEAM_PERIOD;
+        } else if self.algorithm == Algorithm::Steam {
+            self.digits = EasyTotp::DEFAULT_DIGITS;
+            self.period = EasyTotp::DEFAULT_PERIOD;
+        }
+
+        self.algorithm = algorithm;
+        self.totp.take();
+        self
+    }
+
+    /// Sets the number of digits in each generated token
+    ///
+    /// Defaults to 6 (`EasyTotp::DEFAULT_DIGITS`). RFC 6238 allows between 6 and 8 digits.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name)
+    ///     .unwrap()
+    ///     .with_digits(8)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(et.generate_token().unwrap().len(), 8);
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if `digits` is not between 6 and 8 (inclusive), or if the algorithm is `Algorithm::Steam`.
+    pub fn with_digits(mut self, digits: usize) -> Result<Self, EasyTotpError> {
+        if self.algorithm == Algorithm::Steam {
+            return Err(EasyTotpError::InvalidSetting(String::from(
+                "Steam Guard tokens always have 5 characters",
+            )));
+        }
+
+        if !(6..=8).contains(&digits) {
+            return Err(EasyTotpError::InvalidSetting(format!(
+                "digits must be between 6 and 8, not {digits}"
+            )));
+        }
+
+        self.digits = digits;
+        self.totp.take();
+        Ok(self)
+    }
+
+    /// Sets the time step (period) in seconds for which each token is valid
+    ///
+    /// Defaults to 30 seconds (`EasyTotp::DEFAULT_PERIOD`). Authenticator apps read the period from the otpauth URI, so the QR code stays in sync.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name)
+    ///     .unwrap()
+    ///     .with_period(60)
+    ///     .unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if `seconds` is zero, or if the algorithm is `Algorithm::Steam` and `seconds` is not 30.
+    pub fn with_period(mut self, seconds: u64) -> Result<Self, EasyTotpError> {
+        if self.algorithm == Algorithm::Steam && seconds != STEAM_PERIOD {
+            return Err(EasyTotpError::InvalidSetting(String::from(
+                "Steam Guard tokens always have a 30 second period",
+            )));
+        }
+
+        if seconds == 0 {
+            return Err(EasyTotpError::InvalidSetting(String::from(
+                "period must be greater than zero",
+            )));
+        }
+
+        self.period = seconds;
+        self.totp.take();
+        Ok(self)
+    }
+
+    /// Sets the verification skew: the number of time steps before and after the current one that are also accepted
+    ///
+    /// Defaults to 1 (`EasyTotp::DEFAULT_SKEW`), which accepts the previous, current, and next token.
+    ///
+    /// BEWARE: a larger skew weakens security!! Every extra step widens the window in which a stolen or guessed token is accepted.
+    /// Only raise it when clock drift or network latency make it necessary.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name)
+    ///     .unwrap()
+    ///     .with_skew(2);
+    /// ```
+    #[must_use]
+    pub fn with_skew(mut self, steps: u8) -> Self {
+        self.skew = steps;
+        self.totp.take();
+        self
+    }
+
+    /// Sets the minimum secret key length, in bytes, below which the secret is rejected
+    ///
+    /// Defaults to 16 bytes (128 bits), the minimum of RFC 4226, which recommends 20 bytes (160 bits).
+    /// Only lower it to import legacy accounts whose secrets are shorter, e.g. the 10 bytes some older services used.
+    ///
+    /// BEWARE: short secrets are weaker!! Prefer re-enrolling legacy accounts with a fresh secret.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::from_base32_secret("JBSWY3DPEHPK3PXP", issuer, account_name).unwrap();
+    /// assert!(et.generate_token().is_err());
+    ///
+    /// let et = et.with_min_secret_length(10);
+    /// assert!(et.generate_token().is_ok());
+    /// ```
+    #[must_use]
+    pub fn with_min_secret_length(mut self, bytes: usize) -> Self {
+        self.min_secret_len = bytes;
+        self.totp.take();
+        self
+    }
+
+    /// Sets the minimum error-correction level of every QR code: PNG, SVG, and terminal output
+    ///
+    /// Defaults to `QREccLevel::Medium`. Consider `QREccLevel::High` for printed materials that may get smudged.
+    ///
+    /// ```rust
+    /// use easy_totp::{EasyTotp, QREccLevel};
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name)
+    ///     .unwrap()
+    ///     .with_qr_ecc(QREccLevel::High);
+    /// ```
+    #[must_use]
+    pub fn with_qr_ecc(mut self, level: QREccLevel) -> Self {
+        self.qr_ecc = level;
+        self
+    }
+
+    /// Sets the width, in modules, of the light border (quiet zone) around every QR code: PNG, SVG, and terminal output
+    ///
+    /// Defaults to 4, the minimum the QR code specification asks for. Widen it when the QR code is placed on a dark or busy background.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name)
+    ///     .unwrap()
+    ///     .with_quiet_zone(8);
+    /// ```
+    #[must_use]
+    pub fn with_quiet_zone(mut self, modules: u8) -> Self {
+        self.quiet_zone = modules;
+        self
+    }
+
+    /// Sets the text below terminal QR codes, e.g. to translate it or leave it out
+    ///
+    /// Defaults to `QRInstructions::English`. Not saved by `to_encrypted` or compared by `==`, since it belongs to your app rather than to the account.
+    ///
+    /// ```rust
+    /// use easy_totp::{EasyTotp, QRColorMode, QRInstructions, QRSize};
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name)
+    ///     .unwrap()
+    ///     .with_qr_instructions(QRInstructions::Custom(vec![
+    ///         String::from("Scannen Sie den QR-Code mit Ihrer Authenticator-App."),
+    ///         String::from("ACHTUNG: Der QR-Code enthält Ihren geheimen Schlüssel!"),
+    ///     ]));
+    ///
+    /// let lines = et.qr_text(QRSize::Full, QRColorMode::Direct).unwrap();
+    /// assert!(lines.last().unwrap().starts_with("ACHTUNG"));
+    /// ```
+    #[must_use]
+    pub fn with_qr_instructions(mut self, instructions: QRInstructions) -> Self {
+        self.qr_instructions = instructions;
+        self
+    }
+
+    /// Sets how terminal QR codes are shaded
+    ///
+    /// Defaults to `QRShading::Grayscale`. `QRShading::Binary` only draws full blocks and spaces (half blocks in mini size),
+    /// which keeps the contrast high in both color modes if your terminal font or scanner struggles with the shaded blocks.
+    /// `QRShading::Custom` sets your own thresholds for the shaded blocks.
+    /// Not saved by `to_encrypted` or compared by `==`, like `with_qr_instructions`.
+    ///
+    /// ```rust
+    /// use easy_totp::{EasyTotp, QRColorMode, QRInstructions, QRShading, QRSize};
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name)
+    ///     .unwrap()
+    ///     .with_qr_instructions(QRInstructions::Hidden)
+    ///     .with_qr_shading(QRShading::Binary);
+    ///
+    /// let lines = et.qr_text(QRSize::Full, QRColorMode::Inverted).unwrap();
+    /// assert!(lines.iter().flat_map(|line| line.chars()).all(|c| c == '█' || c == ' '));
+    /// ```
+    #[must_use]
+    pub fn with_qr_shading(mut self, shading: QRShading) -> Self {
+        self.qr_shading = shading;
+        self
+    }
+
+    /// Sets an icon for authenticator apps to show next to the account, as the `image` parameter of the otpauth URI and QR code
+    ///
+    /// Only some authenticator apps support this; others ignore it. The URL must use `https://`, so the icon can't be tampered with in transit.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name)
+    ///     .unwrap()
+    ///     .with_icon_url("https://example.com/icon.png")
+    ///     .unwrap();
+    ///
+    /// assert!(et.to_otpauth_uri().unwrap().ends_with("&image=https%3A%2F%2Fexample.com%2Ficon.png"));
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the URL doesn't start with `https://` followed by a host, or contains whitespace or control characters.
+    pub fn with_icon_url(mut self, url: impl Into<String>) -> Result<Self, EasyTotpError> {
+        let url = url.into();
+        validate_icon_url(&url)?;
+
+        self.icon_url = Some(url);
+        Ok(self)
+    }
+
+    /// Returns the underlying `totp_rs::TOTP` instance
+    ///
+    /// Use this to drop down to `totp_rs` for features `easy_totp` doesn't wrap.
+    ///
+    /// BEWARE: the `TOTP` contains the secret key!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let totp = et.to_totp().unwrap();
+    /// assert_eq!(totp.generate(0), et.generate_token_at(0).unwrap());
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the settings are invalid, e.g. the secret key is too short.
+    pub fn to_totp(&self) -> Result<TOTP, EasyTotpError> {
+        Ok(self.totp()?.clone())
+    }
+
+    /// Sets the clock used by every method that reads the current time, e.g. `generate_token` and `verify_token`
+    ///
+    /// Defaults to the system clock. Inject a clock for deterministic tests, or to use a trusted (e.g. NTP-corrected) time source.
+    /// Any `Fn() -> u64` returning a Unix timestamp in seconds is a `Clock`.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name)
+    ///     .unwrap()
+    ///     .with_clock(|| 1_700_000_000);
+    ///
+    /// assert_eq!(et.generate_token().unwrap(), et.generate_token_at(1_700_000_000).unwrap());
+    /// ```
+    #[must_use]
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// The current Unix timestamp, from the injected clock or else the system clock
+    fn now(&self) -> Result<u64, EasyTotpError> {
+        match &self.clock {
+            Some(clock) => Ok(clock.now_unix()),
+            None => unix_time(),
+        }
+    }
+
+    /// Whether `other` generates the same tokens: the same secret key, algorithm, digits, and period
+    ///
+    /// Unlike `==`, this ignores the issuer, account name, and all other settings, which makes it suitable for
+    /// deduplicating accounts merged from sources that label the same secret differently.
+    /// The secret keys are compared in constant time.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+    /// let work = EasyTotp::from_base32_secret(secret, Some(String::from("McCormick")), String::from("test@test-email.com")).unwrap();
+    /// let imported = EasyTotp::from_base32_secret(secret, None, String::from("McCormick (work)")).unwrap();
+    ///
+    /// assert_ne!(work, imported);
+    /// assert!(work.same_credential(&imported));
+    /// ```
+    #[must_use]
+    pub fn same_credential(&self, other: &EasyTotp) -> bool {
+        // Evaluate every comparison, so the time taken does not depend on which of them differ
+        let same_secret = constant_time_eq(&self.raw_secret, &other.raw_secret);
+        let same_format = self.algorithm == other.algorithm
+            && self.digits == other.digits
+            && self.period == other.period;
+
+        same_secret & same_format
+    }
+
+    /// Sets the digits and period read from elsewhere (e.g. an otpauth URI), allowing only the fixed format for Steam Guard
+    fn with_token_format(mut self, digits: usize, period: u64) -> Result<Self, EasyTotpError> {
+        if self.algorithm != Algorithm::Steam {
+            return self.with_digits(digits)?.with_period(period);
+        }
+
+        if digits != STEAM_DIGITS || period != STEAM_PERIOD {
+            return Err(EasyTotpError::InvalidSetting(format!(
+                "Steam Guard tokens have 5 characters and a 30 second period, not {digits} and {period}"
+            )));
+        }
+
+        self.digits = digits;
+        self.period = period;
+        self.totp.take();
+        Ok(self)
+    }
+
+    /// The name of the algorithm for display; `totp_rs` shows Steam Guard as `SHA1`, which it uses internally
+    fn algorithm_name(&self) -> String {
+        if self.algorithm == Algorithm::Steam {
+            String::from("Steam")
+        } else {
+            self.algorithm.to_string()
+        }
+    }
+
+    /// The QR code rendering settings
+    fn qr_options(&self) -> QrOptions {
+        QrOptions {
+            ecc: self.qr_ecc,
+            quiet_zone: self.quiet_zone,
+            shading: self.qr_shading,
+        }
+    }
+
+    /// Returns the cached TOTP instance, building it on first use
+    ///
+    /// Servers that verify many logins with one `EasyTotp` only pay for decoding the secret and constructing the `TOTP` once.
+    fn totp(&self) -> Result<&TOTP, EasyTotpError> {
+        if let Some(totp) = self.totp.get() {
+            return Ok(totp);
+        }
+
+        let totp = self.new_totp()?;
+        Ok(self.totp.get_or_init(|| totp))
+    }
+
+    /// Creates a new TOTP instance
+    fn new_totp(&self) -> Result<TOTP, EasyTotpError> {
+        validate_label(self.issuer.as_deref(), &self.account_name)?;
+
+        let secret = match Secret::Raw(self.raw_secret.to_vec()).to_bytes() {
+            Ok(secret) => secret,
+            Err(e) => return Err(EasyTotpError::SecretParse(e.to_string())),
+        };
+
+        if secret.len() < self.min_secret_len {
+            return Err(EasyTotpError::InvalidSetting(format!(
+                "secret key must be at least {} bytes, not {}",
+                self.min_secret_len,
+                secret.len()
+            )));
+        }
+
+        if self.algorithm == Algorithm::Steam
+            && (self.digits != STEAM_DIGITS || self.period != STEAM_PERIOD)
+        {
+            return Err(EasyTotpError::InvalidSetting(String::from(
+                "Steam Guard tokens have 5 characters and a 30 second period",
+            )));
+        }
+
+        // `TOTP::new` only allows 6 to 8 digits and secrets of at least 128 bits, so Steam Guard tokens and
+        // legacy short secrets (both checked above) skip it
+        if self.algorithm == Algorithm::Steam || secret.len() < MIN_SECRET_LEN {
+            if self.algorithm != Algorithm::Steam && !(6..=8).contains(&self.digits) {
+                return Err(EasyTotpError::InvalidSetting(format!(
+                    "digits must be between 6 and 8, not {}",
+                    self.digits
+                )));
+            }
+
+            return Ok(TOTP::new_unchecked(
+                self.algorithm,
+                self.digits,
+                self.skew,
+                self.period,
+                secret,
+                self.issuer.clone(),
+                self.account_name.clone(),
+            ));
+        }
+
+        let result = TOTP::new(
+            self.algorithm,
+            self.digits,
+            self.skew,
+            self.period,
+            secret,
+            self.issuer.clone(),
+            self.account_name.clone(),
+        );
+
+        match result {
+            Ok(totp) => Ok(totp),
+            Err(e) => Err(EasyTotpError::TotpConstruction(e)),
+        }
+    }
+
+    /// Returns the `otpauth://totp/...` provisioning URI without rendering a QR code
+    ///
+    /// Handy for frontends that render their own QR code, or for deep-linking into an authenticator app.
+    ///
+    /// Following the Key URI Format, the label is `issuer:account_name` (or just `account_name` without an issuer), with both parts percent-encoded,
+    /// and the issuer is repeated in the `issuer` parameter. Since `:` separates the two parts, neither may contain one.
+    /// Non-ASCII names are percent-encoded byte by byte as UTF-8 (e.g. `株` as `%E6%A0%AA`), so the URI itself is always ASCII.
+    ///
+    /// BEWARE: URI contains secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let uri = et.to_otpauth_uri().unwrap();
+    /// assert!(uri.starts_with("otpauth://totp/McCormick:test%40test-email.com?secret="));
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the TOTP instance cannot be created.
+    pub fn to_otpauth_uri(&self) -> Result<String, EasyTotpError> {
+        let mut uri = self.totp()?.get_url();
+
+        // `TOTP` has no notion of icons, so the parameter is added here
+        if let Some(icon_url) = &self.icon_url {
+            uri.push_str("&image=");
+            uri.push_str(&urlencoding::encode(icon_url));
+        }
+
+        Ok(uri)
+    }
+
+    /// Returns the `otpauth://totp/...` provisioning URI with the issuer and account name stripped to ASCII letters, digits, and `punctuation`
+    ///
+    /// For maximum compatibility with constrained scanners, such as field devices that choke on some characters in the label.
+    /// Every other character is dropped, and listed in the returned `SanitizedLabel` along with the label that was encoded.
+    /// Surrounding spaces are trimmed, and an issuer with no characters left is left out. The account itself is unchanged,
+    /// and so are the secret and token format.
+    ///
+    /// `EasyTotp::ASCII_LABEL_PUNCTUATION` keeps email addresses intact. To render the URI as a QR code, pass it to `from_otpauth_uri`.
+    ///
+    /// BEWARE: URI contains secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick™"));
+    /// let account_name = String::from("tëst@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let (uri, label) = et.to_ascii_otpauth_uri(EasyTotp::ASCII_LABEL_PUNCTUATION).unwrap();
+    /// assert!(uri.starts_with("otpauth://totp/McCormick:tst%40test-email.com?secret="));
+    /// assert_eq!(label.dropped, vec!['™', 'ë']);
+    ///
+    /// let qr_png = EasyTotp::from_otpauth_uri(&uri).unwrap().create_qr_png().unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if `punctuation` contains a `:` or a character that isn't printable ASCII,
+    /// if no characters of the account name are kept, or if the TOTP instance cannot be created.
+    pub fn to_ascii_otpauth_uri(
+        &self,
+        punctuation: &str,
+    ) -> Result<(String, SanitizedLabel), EasyTotpError> {
+        if let Some(c) = punctuation
+            .chars()
+            .find(|&c| c == ':' || !(c.is_ascii_graphic() || c == ' '))
+        {
+            return Err(EasyTotpError::InvalidSetting(format!(
+                "label punctuation must be printable ASCII other than ':', not {c:?}"
+            )));
+        }
+
+        let mut dropped = Vec::new();
+        // Spaces next to dropped characters are trimmed, e.g. of "株式会社 McCormick"
+        let mut sanitize = |name: &str| -> String {
+            let kept: String = name
+                .chars()
+                .filter(|&c| {
+                    let keep = c.is_ascii_alphanumeric() || punctuation.contains(c);
+                    if !keep {
+                        dropped.push(c);
+                    }
+                    keep
+                })
+                .collect();
+            String::from(kept.trim())
+        };
+        let issuer = self
+            .issuer
+            .as_deref()
+            .map(&mut sanitize)
+            .filter(|issuer| !issuer.is_empty());
+        let account_name = sanitize(&self.account_name);
+
+        if account_name.is_empty() {
+            return Err(EasyTotpError::InvalidSetting(format!(
+                "no characters of the account name {:?} are ASCII letters, digits, or {punctuation:?}",
+                self.account_name
+            )));
+        }
+
+        let mut sanitized = self.clone();
+        sanitized.issuer.clone_from(&issuer);
+        sanitized.account_name.clone_from(&account_name);
+        sanitized.totp.take();
+
+        Ok((
+            sanitized.to_otpauth_uri()?,
+            SanitizedLabel {
+                issuer,
+                account_name,
+                dropped,
+            },
+        ))
+    }
+
+    /// Returns the QR code as a grid of modules, indexed `[y][x]`, where `true` is a dark module
+    ///
+    /// For custom renderers, such as embedded displays, e-ink, or an HTML canvas. The grid is square, and excludes the quiet zone:
+    /// leave a light border of at least 4 modules (see `with_quiet_zone`) around it when drawing.
+    /// Every built-in renderer draws from this same grid.
+    ///
+    /// BEWARE: QR code contains secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let matrix = et.qr_matrix().unwrap();
+    /// for row in &matrix {
+    ///     let line: String = row.iter().map(|&dark| if dark { '#' } else { ' ' }).collect();
+    ///     println!("{line}");
+    /// }
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation fails.
+    pub fn qr_matrix(&self) -> Result<Vec<Vec<bool>>, EasyTotpError> {
+        module_matrix(&self.to_otpauth_uri()?, self.qr_ecc)
+    }
+
+    /// Returns the QR code as deterministic text, for snapshot tests of rendering, e.g. with `insta` or a golden file
+    ///
+    /// Contains the module matrix (`#` dark, `.` light, without the quiet zone), followed by `qr_text_sized` at 80 columns in
+    /// full and mini size, direct and inverted, without instructions. Each part starts with a `# ...` header line.
+    /// For a stable snapshot, create the account from a fixed secret, e.g. with `new_from_rng` and a seeded RNG.
+    ///
+    /// BEWARE: output contains secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::from_base32_secret("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ", issuer, account_name).unwrap();
+    ///
+    /// let snapshot = et.qr_snapshot().unwrap();
+    /// assert!(snapshot.starts_with("# matrix\n"));
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation or image processing fails.
+    #[cfg(any(test, feature = "snapshot"))]
+    pub fn qr_snapshot(&self) -> Result<String, Box<dyn Error>> {
+        const SNAPSHOT_WIDTH: u32 = 80;
+
+        let mut snapshot = vec![String::from("# matrix")];
+        for row in self.qr_matrix()? {
+            snapshot.push(
+                row.iter()
+                    .map(|&dark| if dark { '#' } else { '.' })
+                    .collect(),
+            );
+        }
+
+        let uri = self.to_otpauth_uri()?;
+        for (size, mode) in [
+            (QRSize::Full, QRColorMode::Direct),
+            (QRSize::Full, QRColorMode::Inverted),
+            (QRSize::Mini, QRColorMode::Direct),
+            (QRSize::Mini, QRColorMode::Inverted),
+        ] {
+            snapshot.push(format!("# {size:?} {mode:?}"));
+            snapshot.extend(qr_text_lines(
+                &uri,
+                self.qr_options(),
+                size,
+                mode,
+                SNAPSHOT_WIDTH,
+                QRAmbiguousWidth::Narrow,
+            )?);
+        }
+
+        Ok(snapshot.into_iter().map(|line| line + "\n").collect())
+    }
+
+    /// Returns the version (1 to 40) of the QR code for this account, which determines how dense it is
+    ///
+    /// A version `v` QR code is `17 + 4 * v` modules wide. Longer issuers and account names, and higher error correction levels
+    /// (see `with_qr_ecc`), need higher versions.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let version = et.qr_version().unwrap();
+    /// assert_eq!(et.qr_matrix().unwrap().len(), 17 + 4 * usize::from(version));
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the TOTP instance cannot be created or the QR code generation fails.
+    pub fn qr_version(&self) -> Result<u8, EasyTotpError> {
+        Ok(encode_qr(&self.to_otpauth_uri()?, self.qr_ecc)?
+            .version()
+            .value())
+    }
+
+    /// Checks that the QR code is at most version `max_version`, so enrollment UIs can suggest a shorter label before it is printed
+    ///
+    /// Returns the QR code version. Dense QR codes have small modules, which cameras struggle with, especially on paper or low-resolution screens.
+    /// `EasyTotp::RECOMMENDED_MAX_QR_VERSION` is a reasonable threshold.
+    ///
+    /// ```rust
+    /// use easy_totp::{EasyTotp, EasyTotpError};
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// match et.check_qr_density(EasyTotp::RECOMMENDED_MAX_QR_VERSION) {
+    ///     Ok(_) => {}
+    ///     Err(EasyTotpError::QrTooDense { .. }) => println!("Please choose a shorter account name"),
+    ///     Err(e) => panic!("{e}"),
+    /// }
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return `EasyTotpError::QrTooDense` if the QR code version is above `max_version`,
+    /// or another error if the TOTP instance cannot be created or the QR code generation fails.
+    pub fn check_qr_density(&self, max_version: u8) -> Result<u8, EasyTotpError> {
+        let version = self.qr_version()?;
+
+        if version > max_version {
+            return Err(EasyTotpError::QrTooDense {
+                version,
+                max_version,
+            });
+        }
+
+        Ok(version)
+    }
+
+    /// Generates QR code text for terminal display, but does not actually print it.
+    ///
+    /// The QR code is scaled to fit the current terminal width, or 80 columns when it can't be detected (e.g. when output is piped).
+    /// Use `qr_text_sized` for a fixed width instead. The QR code is followed by the instructions set with `with_qr_instructions`.
+    ///
+    /// BEWARE: output contains secret!!
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation or image processing fails.
+    pub fn qr_text(&self, size: QRSize, mode: QRColorMode) -> Result<Vec<String>, Box<dyn Error>> {
+        self.qr_text_sized(size, mode, terminal_width())
+    }
+
+    /// Generates QR code text for terminal display, scaled to fit within `target_width` characters, but does not actually print it.
+    ///
+    /// A `target_width` wider than the QR image itself renders one character per pixel.
+    ///
+    /// BEWARE: output contains secret!!
+    ///
+    /// ## Errors
+    /// This function will return an error if `target_width` is zero, or if the QR code generation or image processing fails.
+    pub fn qr_text_sized(
+        &self,
+        size: QRSize,
+        mode: QRColorMode,
+        target_width: u32,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut lines = qr_text_lines(
+            &self.to_otpauth_uri()?,
+            self.qr_options(),
+            size,
+            mode,
+            target_width,
+            QRAmbiguousWidth::Narrow,
+        )?;
+        lines.extend(self.qr_instructions.lines("TOTP"));
+
+        Ok(lines)
+    }
+
+    /// Generates QR code text for terminal display like `qr_text`, for a terminal that draws ambiguous-width characters as `ambiguous_width`
+    ///
+    /// If the block characters would be wider than one cell, the QR code is drawn with `##` and double spaces instead,
+    /// like `qr_text_ascii`, in which case `QRSize::Mini` has no effect.
+    ///
+    /// BEWARE: output contains secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::{EasyTotp, QRAmbiguousWidth, QRColorMode, QRSize};
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let lines = et.qr_text_with_width(QRSize::Full, QRColorMode::Inverted, QRAmbiguousWidth::Wide).unwrap();
+    /// assert!(lines[0].starts_with("##"));
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation or image processing fails.
+    pub fn qr_text_with_width(
+        &self,
+        size: QRSize,
+        mode: QRColorMode,
+        ambiguous_width: QRAmbiguousWidth,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut lines = qr_text_lines(
+            &self.to_otpauth_uri()?,
+            self.qr_options(),
+            size,
+            mode,
+            terminal_width(),
+            ambiguous_width,
+        )?;
+        lines.extend(self.qr_instructions.lines("TOTP"));
+
+        Ok(lines)
+    }
+
+    /// Generates QR code text using only `#` and spaces, but does not actually print it.
+    ///
+    /// Each QR module is drawn as two characters to roughly match its aspect ratio, so even the most minimal terminal
+    /// or font can display it.
+    ///
+    /// BEWARE: output contains secret!!
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation fails.
+    pub fn qr_text_ascii(&self, mode: QRColorMode) -> Result<Vec<String>, EasyTotpError> {
+        let mut lines =
+            qr_module_lines(&self.to_otpauth_uri()?, self.qr_options(), mode, "##", "  ")?;
+        lines.extend(self.qr_instructions.lines("TOTP"));
+
+        Ok(lines)
+    }
+
+    /// Generates QR code text drawn with ANSI background colors, but does not actually print it.
+    ///
+    /// Each QR module is two solid-colored spaces, so the QR code has crisp, square modules regardless of the terminal's font or theme.
+    /// With `truecolor`, pure black and white are used (`ESC[48;2;r;g;bm`); otherwise the basic 16-color palette, which virtually all terminals support.
+    ///
+    /// BEWARE: output contains secret!!
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation fails.
+    pub fn qr_text_ansi(
+        &self,
+        mode: QRColorMode,
+        truecolor: bool,
+    ) -> Result<Vec<String>, EasyTotpError> {
+        let (ink, paper) = if truecolor {
+            ("\x1b[48;2;0;0;0m  ", "\x1b[48;2;255;255;255m  ")
+        } else {
+            ("\x1b[40m  ", "\x1b[107m  ")
+        };
+
+        let mut lines =
+            qr_module_lines(&self.to_otpauth_uri()?, self.qr_options(), mode, ink, paper)?;
+        for line in &mut lines {
+            // Reset the colors so they don't bleed into the rest of the terminal
+            line.push_str("\x1b[0m");
+        }
+        lines.extend(self.qr_instructions.lines("TOTP"));
+
+        Ok(lines)
+    }
+
+    /// Creates a new PNG with a QR code
+    ///
+    /// BEWARE: PNG image contains secret!!
+    ///
+    /// ## Creating a QR code for TOTP setup
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let my_qr_code = et.create_qr_png().unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation or image processing fails.
+    pub fn create_qr_png(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        self.create_qr_image(ImageFormat::Png)
+    }
+
+    /// Creates a new image with a QR code, in any format the `image` crate can encode (e.g. PNG, JPEG, WebP)
+    ///
+    /// Prefer a lossless format such as PNG or WebP: JPEG compression blurs the edges of the QR modules, which can make the QR code harder to scan.
+    ///
+    /// BEWARE: image contains secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::{EasyTotp, ImageFormat};
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let webp_qr_code = et.create_qr_image(ImageFormat::WebP).unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if `format` can't be encoded, or if the QR code generation or image encoding fails.
+    pub fn create_qr_image(&self, format: ImageFormat) -> Result<Vec<u8>, Box<dyn Error>> {
+        if !format.writing_enabled() {
+            return Err(Box::new(EasyTotpError::InvalidSetting(format!(
+                "QR codes can't be encoded as {format:?}"
+            ))));
+        }
+
+        let image = self.create_qr_image_buffer()?;
+
+        let mut buffer = Cursor::new(Vec::new());
+        image.write_to(&mut buffer, format)?;
+
+        Ok(buffer.into_inner())
+    }
+
+    /// Creates a QR code as an unencoded image, for compositing (e.g. a logo in the center), resizing, or watermarking before saving
+    ///
+    /// The image is grayscale, at 8 pixels per QR module, with the quiet zone included. `create_qr_png` and `create_qr_image`
+    /// encode exactly this image. To cover part of the QR code with a logo, raise the error correction level with `with_qr_ecc`.
+    ///
+    /// BEWARE: image contains secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::{EasyTotp, QREccLevel};
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name)
+    ///     .unwrap()
+    ///     .with_qr_ecc(QREccLevel::High);
+    ///
+    /// let mut image = et.create_qr_image_buffer().unwrap().into_rgba8();
+    /// let center = image.width() / 2;
+    /// image.put_pixel(center, center, image::Rgba([255, 0, 0, 255]));
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation fails.
+    pub fn create_qr_image_buffer(&self) -> Result<DynamicImage, Box<dyn Error>> {
+        let image = render_qr_image(&self.to_otpauth_uri()?, self.qr_options(), 8)?;
+
+        Ok(DynamicImage::ImageLuma8(image))
+    }
+
+    /// Creates a new PNG with a QR code, with an error that can be sent across threads
+    fn qr_png_bytes(&self) -> Result<Vec<u8>, EasyTotpError> {
+        let image = render_qr_image(&self.to_otpauth_uri()?, self.qr_options(), 8)?;
+
+        let mut buffer = Vec::new();
+        match image.write_with_encoder(PngEncoder::new(&mut buffer)) {
+            Ok(()) => Ok(buffer),
+            Err(e) => Err(EasyTotpError::ImageDecode(Box::new(e))),
+        }
+    }
+
+    /// Writes a PNG with a QR code directly into `writer`, without an intermediate buffer
+    ///
+    /// BEWARE: PNG image contains secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    /// use std::io::Cursor;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let mut response_body = Cursor::new(Vec::new());
+    /// et.write_qr_png(&mut response_body).unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation, image processing, or writing fails.
+    pub fn write_qr_png<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        let image = render_qr_image(&self.to_otpauth_uri()?, self.qr_options(), 8)?;
+
+        // Encode the image straight into the writer as a PNG
+        image.write_with_encoder(PngEncoder::new(writer))?;
+
+        Ok(())
+    }
+
+    /// Creates a new PNG with a QR code, rendered at `module_px` pixels per QR module
+    ///
+    /// Use a larger `module_px` for print materials. The image includes a white quiet zone on every side (4 modules by default, see `with_quiet_zone`).
+    ///
+    /// BEWARE: PNG image contains secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let printable_qr_code = et.create_qr_png_scaled(20).unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if `module_px` is zero or too large, or if the QR code generation or PNG encoding fails.
+    pub fn create_qr_png_scaled(&self, module_px: u32) -> Result<Vec<u8>, Box<dyn Error>> {
+        let image = render_qr_image(&self.to_otpauth_uri()?, self.qr_options(), module_px)?;
+
+        let mut buffer = Vec::new();
+        image.write_with_encoder(PngEncoder::new(&mut buffer))?;
+
+        Ok(buffer)
+    }
+
+    /// Returns the QR code as a black and white image, rendered directly from the QR matrix at `module_px` pixels per QR module
+    ///
+    /// For e-ink displays and thermal printers: every pixel is either 0 (black) or 255 (white), with no grayscale from resampling,
+    /// so it converts to 1 bit per pixel without re-thresholding. The white quiet zone is included (see `with_quiet_zone`).
+    ///
+    /// BEWARE: image contains secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let image = et.qr_mono_image(3).unwrap();
+    /// assert!(image.pixels().all(|pixel| pixel.0 == [0] || pixel.0 == [255]));
+    ///
+    /// // One bit per pixel for the printer, where `true` is black
+    /// let bits: Vec<bool> = image.pixels().map(|pixel| pixel.0 == [0]).collect();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if `module_px` is zero or too large, or if the QR code generation fails.
+    pub fn qr_mono_image(&self, module_px: u32) -> Result<GrayImage, Box<dyn Error>> {
+        Ok(render_qr_image(
+            &self.to_otpauth_uri()?,
+            self.qr_options(),
+            module_px,
+        )?)
+    }
+
+    /// Creates one PNG with a QR code per entry of `module_pixels`, rendered at that many pixels per QR module
+    ///
+    /// Like calling `create_qr_png_scaled` for each size, e.g. a small inline QR code and a large printable one, but the
+    /// otpauth URI and QR code are only generated once. The PNGs are returned in the same order as `module_pixels`.
+    ///
+    /// BEWARE: PNG images contain secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let pngs = et.create_qr_pngs_multi(&[4, 20]).unwrap();
+    /// let (inline_qr_code, printable_qr_code) = (&pngs[0], &pngs[1]);
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if any entry of `module_pixels` is zero or too large, or if the QR code generation
+    /// or PNG encoding fails.
+    pub fn create_qr_pngs_multi(
+        &self,
+        module_pixels: &[u32],
+    ) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+        let options = self.qr_options();
+        let matrix = module_matrix(&self.to_otpauth_uri()?, options.ecc)?;
+
+        let mut pngs = Vec::with_capacity(module_pixels.len());
+        for &module_px in module_pixels {
+            let mut buffer = Vec::new();
+            render_matrix_image(&matrix, options.quiet_zone, module_px)?
+                .write_with_encoder(PngEncoder::new(&mut buffer))?;
+            pngs.push(buffer);
+        }
+
+        Ok(pngs)
+    }
+
+    /// Creates a new PNG with a QR code in custom colors, e.g. to match a brand palette
+    ///
+    /// `dark` colors the modules and `light` the background and quiet zone, both as `[red, green, blue]`.
+    /// Low-contrast color pairs break scanning, so `dark` must be darker than `light`, with a contrast ratio of at least 3:1
+    /// (as defined by WCAG 2). Even then, prefer near-black modules on a near-white background, and test with the authenticator apps you support.
+    ///
+    /// BEWARE: PNG image contains secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// // Navy on cream
+    /// let branded_qr_code = et.create_qr_png_colored([0, 0, 128], [255, 253, 208]).unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the colors don't contrast enough, or if the QR code generation or PNG encoding fails.
+    pub fn create_qr_png_colored(
+        &self,
+        dark: [u8; 3],
+        light: [u8; 3],
+    ) -> Result<Vec<u8>, Box<dyn Error>> {
+        const MIN_CONTRAST_RATIO: f64 = 3.0;
+
+        // WCAG 2 contrast ratio, from 1 (identical) to 21 (black on white)
+        let (dark_luminance, light_luminance) =
+            (relative_luminance(dark), relative_luminance(light));
+        let contrast = (light_luminance + 0.05) / (dark_luminance + 0.05);
+        if contrast < MIN_CONTRAST_RATIO {
+            return Err(EasyTotpError::InvalidSetting(format!(
+                "dark color {dark:?} must contrast with light color {light:?} by at least {MIN_CONTRAST_RATIO}:1, not {contrast:.2}:1"
+            ))
+            .into());
+        }
+
+        let qr = render_qr_image(&self.to_otpauth_uri()?, self.qr_options(), 8)?;
+        let image = RgbImage::from_fn(qr.width(), qr.height(), |x, y| {
+            Rgb(if qr.get_pixel(x, y)[0] == 0 {
+                dark
+            } else {
+                light
+            })
+        });
+
+        let mut buffer = Vec::new();
+        image.write_with_encoder(PngEncoder::new(&mut buffer))?;
+
+        Ok(buffer)
+    }
+
+    /// Creates a new PNG with a QR code and `logo` in its center, scaled to at most `size_fraction` of the QR code's width
+    ///
+    /// The logo hides some modules, so the QR code always uses `QREccLevel::High` error correction here, regardless of `with_qr_ecc`,
+    /// and `size_fraction` is limited to 0.25. The logo keeps its aspect ratio, and sits on a white background with a margin of one module,
+    /// so transparent logos don't blend into the modules behind them. Always test that the result scans with the authenticator apps you support.
+    ///
+    /// BEWARE: PNG image contains secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::{DynamicImage, EasyTotp};
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let logo = DynamicImage::new_rgba8(64, 64);
+    /// let branded_qr_code = et.create_qr_png_with_logo(&logo, 0.2).unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if `size_fraction` is not greater than 0 and at most 0.25, or if the QR code generation or PNG encoding fails.
+    pub fn create_qr_png_with_logo(
+        &self,
+        logo: &DynamicImage,
+        size_fraction: f32,
+    ) -> Result<Vec<u8>, Box<dyn Error>> {
+        // Hides at most about 6% of the modules, well within the 30% that High error correction recovers
+        const MAX_LOGO_FRACTION: f32 = 0.25;
+        const MODULE_PX: u32 = 8;
+
+        // Written this way round to reject NaN as well
+        if !(size_fraction > 0.0 && size_fraction <= MAX_LOGO_FRACTION) {
+            return Err(EasyTotpError::InvalidSetting(format!(
+                "logo size must be greater than 0 and at most {MAX_LOGO_FRACTION} of the QR code width, not {size_fraction}"
+            ))
+            .into());
+        }
+
+        let options = QrOptions {
+            ecc: QREccLevel::High,
+            ..self.qr_options()
+        };
+        let qr = render_qr_image(&self.to_otpauth_uri()?, options, MODULE_PX)?;
+        let symbol_px = qr.width() - 2 * u32::from(options.quiet_zone) * MODULE_PX;
+
+        // Both factors are positive, and the product is at most a quarter of the image width
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let max_logo_px = ((f64::from(symbol_px) * f64::from(size_fraction)) as u32).max(1);
+        let logo = logo
+            .resize(max_logo_px, max_logo_px, imageops::FilterType::Lanczos3)
+            .to_rgba8();
+
+        // QR images are square
+        let image_size = qr.width();
+        let mut image = DynamicImage::ImageLuma8(qr).to_rgba8();
+        let background = RgbaImage::from_pixel(
+            logo.width() + 2 * MODULE_PX,
+            logo.height() + 2 * MODULE_PX,
+            Rgba([255, 255, 255, 255]),
+        );
+        for layer in [&background, &logo] {
+            imageops::overlay(
+                &mut image,
+                layer,
+                i64::from((image_size - layer.width()) / 2),
+                i64::from((image_size - layer.height()) / 2),
+            );
+        }
+
+        let mut buffer = Vec::new();
+        image.write_with_encoder(PngEncoder::new(&mut buffer))?;
+
+        Ok(buffer)
+    }
+
+    /// Creates a base64-encoded PNG with a QR code, e.g. to send in a JSON response
+    ///
+    /// The standard base64 alphabet with padding, encoding the same bytes as `create_qr_png`. For an HTML `<img>` tag, use `create_qr_data_url`.
+    ///
+    /// BEWARE: base64 string contains secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let json = format!("{{\"qr_code\": \"{}\"}}", et.create_qr_base64().unwrap());
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation or image processing fails.
+    pub fn create_qr_base64(&self) -> Result<String, EasyTotpError> {
+        Ok(general_purpose::STANDARD.encode(self.qr_png_bytes()?))
+    }
+
+    /// Creates a `data:image/png;base64,...` URL with a QR code, ready to inline into an HTML `<img src="...">` tag
+    ///
+    /// Uses the same PNG rendering as `create_qr_png` (dark modules on a white background); `QRColorMode` only applies to terminal output.
+    ///
+    /// BEWARE: data URL contains secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let html = format!("<img src=\"{}\">", et.create_qr_data_url().unwrap());
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation or image processing fails.
+    pub fn create_qr_data_url(&self) -> Result<String, Box<dyn Error>> {
+        Ok(format!(
+            "data:image/png;base64,{}",
+            self.create_qr_base64()?
+        ))
+    }
+
+    /// Creates a new SVG document with a QR code
+    ///
+    /// Unlike `create_qr_png`, the SVG scales crisply to any size, which makes it a good fit for web pages.
+    /// Each dark module is a 1x1 `<rect>`, surrounded by a white quiet zone (4 modules by default, see `with_quiet_zone`).
+    ///
+    /// BEWARE: SVG image contains secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let svg = et.create_qr_svg().unwrap();
+    /// assert!(svg.starts_with("<svg"));
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation fails.
+    pub fn create_qr_svg(&self) -> Result<String, EasyTotpError> {
+        qr_svg(&self.to_otpauth_uri()?, self.qr_options())
+    }
+
+    /// Print the QR code to the terminal
+    ///
+    /// BEWARE: terminal will display secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::{EasyTotp, QRColorMode};
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    /// et.print_qr_to_terminal(QRColorMode::Inverted).unwrap();
+    /// ```
+    ///
+    /// That will print out a QR code in the terminal that you can potentially scan with your authenticator app. Your mileage may vary.
+    ///
+    /// ### Example authenticator apps and whether they can scan from terminal output
+    /// - ✔️ Aegis Authenticator (Android)
+    /// - ✔️ Bitwarden Authenticator (Android)
+    /// - ✔️ Proton Authenticator (Android)
+    /// - ✔️ Google Authenticator (Android)
+    ///
+    /// ```text
+    /// ██████████████████████████████████████████████████████████████████████████████████████████████████████████████████
+    /// ██████████████████████████████████████████████████████████████████████████████████████████████████████████████████
+    /// ██████████████████████████████████████████████████████████████████████████████████████████████████████████████████
+    /// ██████████████████████████████████████████████████████████████████████████████████████████████████████████████████
+    /// ████████              ████        ██      ██      ██████            ████  ██  ████  ████  ██              ████████
+    /// ████████  ██████████  ████████        ██  ██      ██  ██  ██████  ██        ██  ██        ██  ██████████  ████████
+    /// ████████  ██      ██  ██    ██████████████    ██  ██████  ████  ████████  ██  ████████    ██  ██      ██  ████████
+    /// ████████  ██      ██  ██        ██        ████████      ██    ████      ████████    ██  ████  ██      ██  ████████
+    /// ████████  ██      ██  ██  ██████    ██                        ██      ██  ████      ████████  ██      ██  ████████
+    /// ████████  ██████████  ██  ████  ██  ████████████  ██  ██████    ██████████    ████    ██████  ██████████  ████████
+    /// ████████              ██  ██  ██  ██  ██  ██  ██  ██  ██  ██  ██  ██  ██  ██  ██  ██  ██  ██              ████████
+    /// ████████████████████████    ██    ██  ████  ██        ██████  ██████    ████    ████████  ████████████████████████
+    /// ████████  ██          ████  ████  ██████    ████              ████████  ████████          ██          ████████████
+    /// ████████  ████      ██    ██  ████  ████        ██  ██████  ████        ████████    ██████          ██████████████
+    /// ████████████████████  ██    ██          ██████  ██      ██      ████  ████  ██    ██  ██    ██    ██      ████████
+    /// ██████████    ██    ████        ██████  ██      ██  ██  ██  ████  ██████  ██████    ██  ██    ████████    ████████
+    /// ████████  ██████      ████  ████  ██  ████      ██    ██      ████      ██        ████  ████    ██████  ██████████
+    /// ██████████  ██████  ████      ████      ██    ████  ██      ██████      ██████  ██  ██████    ██  ██    ██████████
+    /// ████████████████  ██      ██████    ████        ████████  ████████  ██          ██████  ██████████████    ████████
+    /// ████████████████    ██  ████    ██      ██    ████  ████  ██  ██████    ██  ██    ██  ██  ██  ██  ████  ██████████
+    /// ██████████████  ████  ████████████████    ████    ████    ████  ██    ██████████      ██    ██  ██  ██  ██████████
+    /// ████████  ██  ██  ██████  ██████████████    ██  ████████    ████████    ██  ██    ████████    ██  ████  ██████████
+    /// ████████████████        ████        ██████  ██      ██    ████  ██    ██████████  ██████  ████      ████  ████████
+    /// ██████████    ██  ████  ██  ██  ██████    ██  ████████  ████████████        ████    ████          ████████████████
+    /// ████████      ██████  ████        ██████  ██  ████    ██    ██████  ██  ████      ████    ████  ████  ██  ████████
+    /// ████████████  ██    ██    ██  ██████  ████      ████  ██  ████████        ██████    ██  ██      ██  ██  ██████████
+    /// ████████████              ██        ██      ██    ██          ██  ████      ████████  ██          ██████  ████████
+    /// ████████████  ██  ██████    ██    ████  ████    ████  ██████  ████████████      ██  ████  ██████  ████    ████████
+    /// ████████    ██    ██  ██    ██    ██  ██████  ██      ██  ██  ████  ██  ████        ████  ██  ██      ████████████
+    /// ████████████      ██████  ██  ████  ██    ██████████  ██████          ██  ████      ████  ██████    ██████████████
+    /// ██████████                ████        ██    ██████            ██        ██  ██    ██              ██      ████████
+    /// ████████    ██████  ██  ██      ████  ██  ██    ██          ██        ██    ██████    ██  ██    ██████    ████████
+    /// ████████████  ██            ██████  ██      ████████        ████████    ████████          ██  ██    ██    ████████
+    /// ██████████  ██    ████        ██  ██        ██      ████  ████  ██    ██  ██████    ██  ██████  ████  ████████████
+    /// ████████      ██  ██        ██    ██  ██  ██████  ██      ████████████████    ██  ████████        ████    ████████
+    /// ████████  ██        ████████  ████████  ██████    ██████  ██    ██  ████████  ██          ████████████    ████████
+    /// ████████  ████  ████  ████  ████  ██      ████    ██    ██  ████  ████          ████    ██████          ██████████
+    /// ████████  ██      ████  ██████    ██  ██  ████  ████████            ████  ██  ████    ██████  ████  ██  ██████████
+    /// ████████████  ██      ██      ████    ████████            ██████  ██        ██  ████████  ██  ██    ██    ████████
+    /// ████████  ██    ██  ██            ████  ██  ██████  ██        ██  ██████    ██        ████  ██  ██████    ████████
+    /// ████████  ██      ██  ████  ████    ██  ██      ██        ████  ██  ██  ████                ████          ████████
+    /// ████████  ██        ██      ██      ██████  ██  ██          ████████      ████  ██  ██      ██████        ████████
+    /// ██████████  ██████      ████████  ██    ██  ████  ██  ██  ██████    ██  ██    ██      ████    ████████    ████████
+    /// ██████████      ████████  ██    ██  ██        ██                ████    ████  ████████████████    ████  ██████████
+    /// ████████      ██████    ██████  ████████    ██                ████  ██  ██                            ████████████
+    /// ████████████████████████  ██████  ████          ████  ██████  ██          ████████  ██    ██████  ██  ████████████
+    /// ████████              ████        ██  ████  ██        ██  ██      ██  ██    ████      ██  ██  ██  ████    ████████
+    /// ████████  ██████████  ██    ██████████████████████    ██████  ██  ████    ██    ████      ██████  ████    ████████
+    /// ████████  ██      ██  ██      ████  ██  ██████  ████          ██████    ██  ██    ██  ██          ████  ██████████
+    /// ████████  ██      ██  ██  ████  ████████    ██████  ██  ██                ██████    ██              ██  ██████████
+    /// ████████  ██      ██  ██    ██    ██  ████      ██    ██  ██      ████              ██████  ██  ████      ████████
+    /// ████████  ██████████  ████                  ██████████        ██████    ██  ██    ████████      ████████  ████████
+    /// ████████              ██  ████    ██  ██  ██      ████    ████  ██    ██████████    ████      ████  ██    ████████
+    /// ██████████████████████████████████████████████████████████████████████████████████████████████████████████████████
+    /// ██████████████████████████████████████████████████████████████████████████████████████████████████████████████████
+    /// ██████████████████████████████████████████████████████████████████████████████████████████████████████████████████
+    /// ██████████████████████████████████████████████████████████████████████████████████████████████████████████████████
+    /// Scan the above QR code with your authenticator app to set up TOTP.
+    /// BEWARE: this QR code contains your secret key! Handle with care.
+    /// Useful tips: if scanning fails, try inverting the QR code colors by adjusting your terminal's background color or
+    /// using your mouse to select the entire QR code area. Also, ensure your terminal zoom is set to a level that allows
+    /// the QR code to be completely visible onscreen.
+    ///                                               
+    /// ```
+    /// ## Errors
+    /// This function will return an error if the QR code generation or terminal rendering fails.
+    pub fn print_qr_to_terminal(&self, user_mode: QRColorMode) -> Result<(), Box<dyn Error>> {
+        self.write_qr_terminal(&mut stdout().lock(), user_mode, QRSize::Full)
+    }
+
+    /// Writes the terminal rendering of the QR code to `writer`, one line at a time, instead of printing it
+    ///
+    /// Produces exactly what `print_qr_to_terminal` (with `QRSize::Full`) and `print_mini_qr_to_terminal` (with `QRSize::Mini`) print,
+    /// so it can be captured into a `Vec<u8>` for tests, or sent over a socket.
+    ///
+    /// Terminal QR codes have received mixed results depending on the authenticator app used (Aegis seems to work well,
+    /// whereas Proton Authenticator has trouble scanning from terminal). Your mileage may vary.
+    ///
+    /// BEWARE: output contains secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::{EasyTotp, QRColorMode, QRSize};
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let mut output = Vec::new();
+    /// et.write_qr_terminal(&mut output, QRColorMode::Inverted, QRSize::Mini).unwrap();
+    /// let rendered = String::from_utf8(output).unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation or writing fails.
+    pub fn write_qr_terminal<W: Write>(
+        &self,
+        writer: &mut W,
+        mode: QRColorMode,
+        size: QRSize,
+    ) -> Result<(), Box<dyn Error>> {
+        write_lines(writer, &self.qr_text(size, mode)?)?;
+
+        Ok(())
+    }
+
+    /// Prints the QR code, the base32 secret key, and the otpauth URI to stdout in one go, and returns them
+    ///
+    /// Covers the usual enrollment flow of command-line tools: users scan the QR code, or type in the secret key
+    /// if their app can't scan. The returned `EnrollmentInfo` lets callers format the same values differently.
+    ///
+    /// BEWARE: terminal will display secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::{EasyTotp, QRColorMode};
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let info = et.enroll(QRColorMode::Direct).unwrap();
+    /// assert_eq!(info.secret_base32, et.secret_base32());
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the TOTP instance cannot be created or the QR code generation fails.
+    pub fn enroll(&self, mode: QRColorMode) -> Result<EnrollmentInfo, Box<dyn Error>> {
+        let info = EnrollmentInfo {
+            uri: self.to_otpauth_uri()?,
+            secret_base32: self.secret_base32(),
+            qr_lines: self.qr_text(QRSize::Full, mode)?,
+        };
+        println!("{info}");
+
+        Ok(info)
+    }
+
+    /// Former, misspelled name of [`EasyTotp::print_qr_to_terminal`]
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation or terminal rendering fails.
+    #[deprecated(since = "0.6.0", note = "renamed to `print_qr_to_terminal`")]
+    pub fn print_qr_to_teminal(&self, user_mode: QRColorMode) -> Result<(), Box<dyn Error>> {
+        self.print_qr_to_terminal(user_mode)
+    }
+
+    /// Print the QR code to the terminal using only ASCII characters, for terminals without Unicode block characters
+    ///
+    /// BEWARE: terminal will display secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::{EasyTotp, QRColorMode};
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    /// et.print_qr_to_terminal_ascii(QRColorMode::Inverted).unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation fails.
+    pub fn print_qr_to_terminal_ascii(&self, user_mode: QRColorMode) -> Result<(), EasyTotpError> {
+        match write_lines(&mut stdout().lock(), &self.qr_text_ascii(user_mode)?) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(EasyTotpError::Io(e)),
+        }
+    }
+
+    /// Print the QR code to the terminal using ANSI background colors, for the most reliable scanning
+    ///
+    /// Truecolor is used when the `COLORTERM` environment variable says the terminal supports it, and the basic 16-color palette otherwise.
+    /// Use `print_qr_to_terminal_ascii` for terminals without any color support.
+    ///
+    /// BEWARE: terminal will display secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::{EasyTotp, QRColorMode};
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    /// et.print_qr_to_terminal_ansi(QRColorMode::Direct).unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation fails.
+    pub fn print_qr_to_terminal_ansi(&self, user_mode: QRColorMode) -> Result<(), EasyTotpError> {
+        let truecolor = std::env::var("COLORTERM")
+            .is_ok_and(|colorterm| colorterm == "truecolor" || colorterm == "24bit");
+
+        match write_lines(
+            &mut stdout().lock(),
+            &self.qr_text_ansi(user_mode, truecolor)?,
+        ) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(EasyTotpError::Io(e)),
+        }
+    }
+
+    /// Print the QR code to the terminal, scaled to fit within `target_width` characters
+    ///
+    /// BEWARE: terminal will display secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::{EasyTotp, QRColorMode};
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    /// et.print_qr_to_terminal_sized(QRColorMode::Inverted, 80).unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if `target_width` is zero, or if the QR code generation or terminal rendering fails.
+    pub fn print_qr_to_terminal_sized(
+        &self,
+        user_mode: QRColorMode,
+        target_width: u32,
+    ) -> Result<(), Box<dyn Error>> {
+        write_lines(
+            &mut stdout().lock(),
+            &self.qr_text_sized(QRSize::Full, user_mode, target_width)?,
+        )?;
+
+        Ok(())
+    }
+
+    /// Print the QR code to the terminal, for a terminal that draws ambiguous-width characters as `ambiguous_width`
+    ///
+    /// Use `QRAmbiguousWidth::Wide` if the QR code looks stretched, as happens in many terminals configured for CJK locales.
+    /// See `qr_text_with_width`.
+    ///
+    /// BEWARE: terminal will display secret!!
+    ///
+    /// ```rust
+    /// use easy_totp::{EasyTotp, QRAmbiguousWidth, QRColorMode, QRSize};
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    /// et.print_qr_to_terminal_with_width(QRSize::Full, QRColorMode::Inverted, QRAmbiguousWidth::Wide).unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation or terminal rendering fails.
+    pub fn print_qr_to_terminal_with_width(
+        &self,
+        size: QRSize,
+        user_mode: QRColorMode,
+        ambiguous_width: QRAmbiguousWidth,
+    ) -> Result<(), Box<dyn Error>> {
+        write_lines(
+            &mut stdout().lock(),
+            &self.qr_text_with_width(size, user_mode, ambiguous_width)?,
+        )?;
+
+        Ok(())
+    }
+
+    /// Print the mini QR code to the terminal
+    ///
+    /// BEWARE: terminal will display secret!!
+    ///
+    /// The mini QR code uses half-block characters (`▀` and `▄`) so that each line of text holds two rows of the QR code,
+    /// halving its height compared to `print_qr_to_terminal`.
+    ///
+    /// ```rust
+    /// use easy_totp::{EasyTotp, QRColorMode};
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    /// et.print_mini_qr_to_terminal(QRColorMode::Inverted).unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the QR code generation or terminal rendering fails.
+    pub fn print_mini_qr_to_terminal(&self, user_mode: QRColorMode) -> Result<(), Box<dyn Error>> {
+        self.write_qr_terminal(&mut stdout().lock(), user_mode, QRSize::Mini)
+    }
+
+    /// Generates a TOTP token for authentication
+    ///
+    /// ## Errors
+    /// This function will return an error if the TOTP generation fails.
+    pub fn generate_token(&self) -> Result<String, Box<dyn Error>> {
+        self.generate_token_at(self.now()?)
+    }
+
+    /// Generates a TOTP token for an arbitrary Unix timestamp (in seconds)
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let token = et.generate_token_at(1_700_000_000).unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the TOTP instance cannot be created.
+    pub fn generate_token_at(&self, unix_time: u64) -> Result<String, Box<dyn Error>> {
+        Ok(self.totp()?.generate(unix_time))
+    }
+
+    /// Generates a TOTP token from this secret key with another algorithm, number of digits, and period, at an arbitrary Unix timestamp (in seconds)
+    ///
+    /// A low-level escape hatch for one-off variants, e.g. both a SHA1 and a SHA512 token for apps with differing support,
+    /// without cloning and reconfiguring the `EasyTotp`. This ignores the configured algorithm, digits, and period entirely,
+    /// and leaves the cached `TOTP` instance alone. For Steam Guard, pass 5 digits and a 30 second period.
+    ///
+    /// ```rust
+    /// use easy_totp::{Algorithm, EasyTotp};
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::from_base32_secret("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ", issuer, account_name).unwrap();
+    ///
+    /// // RFC 6238 test vector
+    /// assert_eq!(et.generate_token_with(Algorithm::SHA1, 8, 30, 59).unwrap(), "94287082");
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the digits or period are invalid (see `with_digits` and `with_period`),
+    /// or the secret key is shorter than the minimum length.
+    pub fn generate_token_with(
+        &self,
+        algorithm: Algorithm,
+        digits: usize,
+        period: u64,
+        unix_time: u64,
+    ) -> Result<String, EasyTotpError> {
+        // Only the secret matters, so the label is left out rather than validated
+        let variant = EasyTotp {
+            raw_secret: self.raw_secret.clone(),
+            min_secret_len: self.min_secret_len,
+            ..Default::default()
+        }
+        .with_algorithm(algorithm)
+        .with_token_format(digits, period)?;
+
+        Ok(variant.new_totp()?.generate(unix_time))
+    }
+
+    /// Generates a TOTP token for display, split in the middle by `separator`, e.g. `123 456` or `1234 5678`
+    ///
+    /// Grouped tokens are easier to read and type. Only show them: verify the token the user enters with `verify_token`,
+    /// after removing the separator. Odd lengths put the extra character in the second group, e.g. `123 4567`.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let grouped = et.generate_token_grouped(" ").unwrap();
+    /// assert_eq!(grouped.len(), 7);
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the TOTP generation fails.
+    pub fn generate_token_grouped(&self, separator: &str) -> Result<String, Box<dyn Error>> {
+        self.generate_token_grouped_at(self.now()?, separator)
+    }
+
+    /// Generates a TOTP token for display like `generate_token_grouped`, for an arbitrary Unix timestamp (in seconds)
+    ///
+    /// ## Errors
+    /// This function will return an error if the TOTP instance cannot be created.
+    pub fn generate_token_grouped_at(
+        &self,
+        unix_time: u64,
+        separator: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let token = self.generate_token_at(unix_time)?;
+
+        // Tokens are ASCII, so every index is a character boundary
+        let (first, second) = token.split_at(token.len() / 2);
+        Ok(format!("{first}{separator}{second}"))
+    }
+
+    /// Returns how many seconds the current token remains valid, in the range `1..=period`
+    ///
+    /// Useful for rendering a countdown next to the current token.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let remaining = et.seconds_remaining().unwrap();
+    /// assert!((1..=30).contains(&remaining));
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the TOTP instance cannot be created or the system time is before the Unix epoch.
+    pub fn seconds_remaining(&self) -> Result<u64, EasyTotpError> {
+        // Build the `TOTP` instance anyway, so invalid settings are reported consistently
+        self.totp()?;
+
+        Ok(self.period - self.now()? % self.period)
+    }
+
+    /// Generates the token for the current time step, along with how long it remains valid and the step counter
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let info = et.token_info().unwrap();
+    /// println!("{} (valid for {}s, step {})", info.code, info.seconds_remaining, info.step);
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the TOTP instance cannot be created or the system time is before the Unix epoch.
+    pub fn token_info(&self) -> Result<TokenInfo, Box<dyn Error>> {
+        // Read the clock once, so all three values describe the same step
+        let now = self.now()?;
+
+        Ok(TokenInfo {
+            code: self.generate_token_at(now)?,
+            seconds_remaining: self.period - now % self.period,
+            step: now / self.period,
+        })
+    }
+
+    /// Generates the token for the current time step, and the token for the following one
+    ///
+    /// Authenticator-style UIs can preview the next token near the end of a step, alongside `seconds_remaining`.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let (current, next) = et.current_and_next_token().unwrap();
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the TOTP instance cannot be created or the system time is before the Unix epoch.
+    pub fn current_and_next_token(&self) -> Result<(String, String), Box<dyn Error>> {
+        // Read the clock once, so both tokens come from adjacent steps even across a step boundary
+        let now = self.now()?;
+
+        Ok((
+            self.generate_token_at(now)?,
+            self.generate_token_at(now.saturating_add(self.period))?,
+        ))
+    }
+
+    /// DIAGNOSTIC ONLY: generates every token that `verify_token` currently accepts, oldest first
+    ///
+    /// Covers the steps from `skew` before the current one to `skew` after it (clipped at the Unix epoch), which helps
+    /// track down why a user's token is rejected, e.g. clock drift on their device.
+    /// Never use this to verify tokens (use `verify_token`, which compares in constant time), and never log its output in production:
+    /// every entry is a valid login code.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// // With the default skew of 1: the previous, current, and next tokens
+    /// let tokens = et.acceptable_tokens().unwrap();
+    /// assert_eq!(tokens.len(), 3);
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the TOTP instance cannot be created or the system time is before the Unix epoch.
+    pub fn acceptable_tokens(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        self.acceptable_tokens_at(self.now()?)
+    }
+
+    /// DIAGNOSTIC ONLY: generates every token that `verify_token_at` accepts at an arbitrary Unix timestamp (in seconds), oldest first
+    ///
+    /// See `acceptable_tokens`. BEWARE: never log the output in production!!
+    ///
+    /// ## Errors
+    /// This function will return an error if the TOTP instance cannot be created.
+    pub fn acceptable_tokens_at(&self, unix_time: u64) -> Result<Vec<String>, Box<dyn Error>> {
+        let totp = self.totp()?;
+        let step = unix_time / self.period;
+        let skew = u64::from(self.skew);
+
+        // Like `verify_token_at`, the window stops at the Unix epoch
+        Ok((step.saturating_sub(skew)..=step.saturating_add(skew))
+            .map(|s| totp.generate(s.saturating_mul(self.period)))
+            .collect())
+    }
+
+    /// DIAGNOSTIC ONLY: finds out how many time steps the clock of the user's device is off by, e.g. after a rejected token
+    ///
+    /// Searches 10 steps either side of the current one and returns the offset of the matching step: positive if the device is ahead,
+    /// negative if it is behind, e.g. `Some(6)` for a device 3 minutes fast with the default 30 second period. Returns `None` if nothing matches,
+    /// including for malformed tokens. When several steps match, the one closest to the current step wins.
+    ///
+    /// BEWARE: never treat a match as a successful login!! The window is far wider than `verify_token` allows, which makes guessing easier.
+    /// Only use the result to tell the user to fix their clock.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// // A device whose clock is 3 minutes fast
+    /// let token = et.generate_token_at(1_700_000_000 + 180).unwrap();
+    /// assert_eq!(et.diagnose_token_at(&token, 1_700_000_000).unwrap(), Some(6));
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the TOTP instance cannot be created or the system time is unavailable.
+    pub fn diagnose_token(&self, token: &str) -> Result<Option<i64>, Box<dyn Error>> {
+        self.diagnose_token_at(token, self.now()?)
+    }
+
+    /// DIAGNOSTIC ONLY: finds out how many time steps the clock of the user's device is off by, at an arbitrary Unix timestamp (in seconds)
+    ///
+    /// See `diagnose_token`. BEWARE: never treat a match as a successful login!!
+    ///
+    /// ## Errors
+    /// This function will return an error if the TOTP instance cannot be created.
+    pub fn diagnose_token_at(
+        &self,
+        token: &str,
+        unix_time: u64,
+    ) -> Result<Option<i64>, Box<dyn Error>> {
+        if !self.is_well_formed_token(token) {
+            return Ok(None);
+        }
+
+        let totp = self.totp()?;
+        let step = unix_time / self.period;
+
+        // Closest steps first: 0, -1, 1, -2, 2, ...
+        let offsets = (0..=DRIFT_DIAGNOSIS_STEPS)
+            .flat_map(|distance| [-distance, distance])
+            .skip(1);
+        for offset in offsets {
+            // Like `verify_token_at`, the window stops at the Unix epoch
+            let Some(s) = step.checked_add_signed(offset) else {
+                continue;
+            };
+            if constant_time_eq(
+                totp.generate(s.saturating_mul(self.period)).as_bytes(),
+                token.as_bytes(),
+            ) {
+                return Ok(Some(offset));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Verifies a user-submitted TOTP token against the current time step
+    ///
+    /// Returns `Ok(true)` if the token matches within the skew window, and `Ok(false)` otherwise.
+    /// Malformed tokens (anything other than the configured number of ASCII digits) are rejected with `Ok(false)` rather than an error.
+    ///
+    /// Well-formed tokens are compared in constant time, so the time taken does not reveal how many digits matched.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let token = et.generate_token().unwrap();
+    /// assert!(et.verify_token(&token).unwrap());
+    /// assert!(!et.verify_token("not a token").unwrap());
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the TOTP instance cannot be created or the system time is unavailable.
+    pub fn verify_token(&self, token: &str) -> Result<bool, Box<dyn Error>> {
+        Ok(self.verify_token_at(token, self.now()?)?)
+    }
+
+    /// Verifies a user-submitted TOTP token against an arbitrary Unix timestamp (in seconds)
+    ///
+    /// The skew window is applied around `unix_time`, not around the system time, which makes this suitable for
+    /// deterministic tests and for servers with their own trusted time source.
+    /// Like `verify_token`, well-formed tokens are compared in constant time.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let token = et.generate_token_at(1_700_000_000).unwrap();
+    /// assert!(et.verify_token_at(&token, 1_700_000_000).unwrap());
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the TOTP instance cannot be created.
+    pub fn verify_token_at(&self, token: &str, unix_time: u64) -> Result<bool, EasyTotpError> {
+        if !self.is_well_formed_token(token) {
+            return Ok(false);
+        }
+
+        // Only the token's length and character set are checked before this point, both of which are public anyway.
+        // Every comparison against a generated code from here on is constant-time: `TOTP::check` uses
+        // `constant_time_eq` internally, and the epoch fallback below calls it directly. Never compare with `==`.
+        let totp = self.totp()?;
+        let step = unix_time / self.period;
+
+        // `TOTP::check` underflows when the skew window reaches back before the Unix epoch, so clip the window there
+        if step < u64::from(self.skew) {
+            let last_step = step + u64::from(self.skew);
+            return Ok((0..=last_step).any(|s| {
+                constant_time_eq(totp.generate(s * self.period).as_bytes(), token.as_bytes())
+            }));
+        }
+
+        Ok(totp.check(token, unix_time))
+    }
+
+    /// Verifies a user-submitted TOTP token like `verify_token`, but accepts each time step at most once
+    ///
+    /// A token stays valid for its whole time step (and the skew window), so an attacker who sees it can replay it.
+    /// This accepts the token only if its time step is later than `last_used_step`, and then records that step there.
+    ///
+    /// BEWARE: persist `last_used_step` per account (e.g. next to the secret), and update it atomically, or replays remain possible!!
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// // Load this from storage; `None` means no token was accepted yet
+    /// let mut last_used_step = None;
+    ///
+    /// let token = et.generate_token().unwrap();
+    /// assert!(et.verify_token_once(&token, &mut last_used_step).unwrap());
+    /// assert!(!et.verify_token_once(&token, &mut last_used_step).unwrap());
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the TOTP instance cannot be created or the system time is before the Unix epoch.
+    pub fn verify_token_once(
+        &self,
+        token: &str,
+        last_used_step: &mut Option<u64>,
+    ) -> Result<bool, EasyTotpError> {
+        self.verify_token_once_at(token, self.now()?, last_used_step)
+    }
+
+    /// Verifies a user-submitted TOTP token against an arbitrary Unix timestamp (in seconds), accepting each time step at most once
+    ///
+    /// See `verify_token_once` and `verify_token_at`.
+    ///
+    /// ## Errors
+    /// This function will return an error if the TOTP instance cannot be created.
+    pub fn verify_token_once_at(
+        &self,
+        token: &str,
+        unix_time: u64,
+        last_used_step: &mut Option<u64>,
+    ) -> Result<bool, EasyTotpError> {
+        if !self.is_well_formed_token(token) {
+            return Ok(false);
+        }
+
+        let totp = self.totp()?;
+        let step = unix_time / self.period;
+        let first_step = step.saturating_sub(u64::from(self.skew));
+        let last_step = step.saturating_add(u64::from(self.skew));
+
+        // Check every step of the window in constant time, without exiting early, and keep the latest match
+        let mut matched = None;
+        for s in first_step..=last_step {
+            if constant_time_eq(
+                totp.generate(s.saturating_mul(self.period)).as_bytes(),
+                token.as_bytes(),
+            ) {
+                matched = Some(s);
+            }
+        }
+
+        match (matched, *last_used_step) {
+            (Some(s), Some(last)) if s <= last => Ok(false),
+            (Some(s), _) => {
+                *last_used_step = Some(s);
+                Ok(true)
+            }
+            (None, _) => Ok(false),
+        }
+    }
+
+    /// Whether `token` has the configured number of digits, and nothing else
+    fn is_well_formed_token(&self, token: &str) -> bool {
+        if self.algorithm == Algorithm::Steam {
+            return token.len() == self.digits && token.chars().all(|c| STEAM_CHARS.contains(c));
+        }
+
+        token.len() == self.digits && token.chars().all(|c| c.is_ascii_digit())
+    }
+}
+
+/// How many time steps either side of the current one `diagnose_token` searches
+#[cfg(feature = "std")]
+const DRIFT_DIAGNOSIS_STEPS: i64 = 10;
+
+/// Length in bytes of randomly generated secret keys: 160 bits, as recommended by RFC 4226
+#[cfg(feature = "std")]
+const DEFAULT_SECRET_LEN: usize = 20;
+
+/// Minimum length in bytes of a secret key: 128 bits, as required by RFC 4226
+const MIN_SECRET_LEN: usize = 16;
+
+/// Stands in for a secret key in `Debug` output, showing only its length, e.g. `<redacted 20 bytes>`
+///
+/// Never print the secret, since `Debug` output tends to end up in logs and panic messages.
+pub(crate) struct RedactedSecret(usize);
+
+impl core::fmt::Debug for RedactedSecret {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "<redacted {} bytes>", self.0)
+    }
+}
+
+/// Formats a secret key for `Debug` output without revealing it; see `RedactedSecret`
+pub(crate) fn redacted_secret(secret: &[u8]) -> RedactedSecret {
+    RedactedSecret(secret.len())
+}
+
+/// Number of characters in each Steam Guard token
+#[cfg(feature = "std")]
+const STEAM_DIGITS: usize = 5;
+
+/// Time step of Steam Guard tokens, in seconds
+#[cfg(feature = "std")]
+const STEAM_PERIOD: u64 = 30;
+
+/// Issuer of every Steam Guard account
+#[cfg(feature = "std")]
+const STEAM_ISSUER: &str = "Steam";
+
+/// The characters Steam Guard tokens are made of
+#[cfg(feature = "std")]
+const STEAM_CHARS: &str = "23456789BCDFGHJKMNPQRTVWXY";
+
+/// Whether `name` is a `.` or `..` path segment, which URL parsing resolves away
+#[cfg(feature = "std")]
+fn is_dot_segment(name: &str) -> bool {
+    name == "." || name == ".."
+}
+
+/// Checks that the issuer and account name can form an unambiguous `issuer:account_name` otpauth label
+///
+/// Other special characters (e.g. `/`, `?`, or spaces) are fine, since they are percent-encoded in the URI.
+/// Names that are just `.` or `..` are not: URL parsing drops such path segments even when percent-encoded,
+/// so their URIs wouldn't read back as the same account.
+#[cfg(feature = "std")]
+fn validate_label(issuer: Option<&str>, account_name: &str) -> Result<(), EasyTotpError> {
+    if issuer.is_some_and(is_dot_segment) || is_dot_segment(account_name) {
+        return Err(EasyTotpError::InvalidSetting(String::from(
+            "issuer and account name must not be '.' or '..', which URL parsing drops from the otpauth URI",
+        )));
+    }
+
+    if issuer.is_some_and(|issuer| issuer.contains(':')) {
+        return Err(EasyTotpError::InvalidSetting(String::from(
+            "issuer must not contain ':', which separates it from the account name",
+        )));
+    }
+
+    if account_name.contains(':') {
+        return Err(EasyTotpError::InvalidSetting(String::from(
+            "account name must not contain ':', which separates it from the issuer",
+        )));
+    }
+
+    Ok(())
+}
+
+/// Checks that the account name, and the issuer if there is one, aren't empty or whitespace-only
+///
+/// Such labels produce otpauth URIs like `otpauth://totp/Issuer:?secret=...`, which some authenticator apps refuse to import.
+/// Only new credentials are checked, so that existing ones with blank names can still be imported and used.
+#[cfg(feature = "std")]
+fn validate_names_not_blank(issuer: Option<&str>, account_name: &str) -> Result<(), EasyTotpError> {
+    if issuer.is_some_and(|issuer| issuer.trim().is_empty()) {
+        return Err(EasyTotpError::InvalidSetting(String::from(
+            "issuer must not be empty; use None to leave it out",
+        )));
+    }
+
+    if account_name.trim().is_empty() {
+        return Err(EasyTotpError::InvalidSetting(String::from(
+            "account name must not be empty",
+        )));
+    }
+
+    Ok(())
+}
+
+/// Checks the query of an untrusted otpauth URI for what `TOTP::from_url` lets through: repeated parameters,
+/// of which it silently keeps the last, and percent-encoding that isn't UTF-8, which it decodes lossily
+#[cfg(feature = "std")]
+fn validate_uri_params(uri: &str) -> Result<(), EasyTotpError> {
+    // The largest QR code holds 2953 bytes, so anything longer can't have been scanned
+    const MAX_URI_LEN: usize = 2953;
+
+    if uri.len() > MAX_URI_LEN {
+        return Err(EasyTotpError::InvalidUri(format!(
+            "URI is {} bytes long, more than a QR code can hold",
+            uri.len()
+        )));
+    }
+
+    let Some((_, query)) = uri.split_once('?') else {
+        return Ok(());
+    };
+
+    let mut seen: Vec<&str> = Vec::new();
+    for param in query.split('&').filter(|param| !param.is_empty()) {
+        let (key, value) = param.split_once('=').unwrap_or((param, ""));
+
+        if seen.contains(&key) {
+            return Err(EasyTotpError::InvalidUri(format!(
+                "parameter {key:?} appears more than once"
+            )));
+        }
+        seen.push(key);
+
+        if urlencoding::decode(key).is_err() || urlencoding::decode(value).is_err() {
+            return Err(EasyTotpError::InvalidUri(format!(
+                "parameter {key:?} is not valid percent-encoded UTF-8"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that an icon URL is a plain `https://` URL
+#[cfg(feature = "std")]
+fn validate_icon_url(url: &str) -> Result<(), EasyTotpError> {
+    let Some(rest) = url.strip_prefix("https://") else {
+        return Err(EasyTotpError::InvalidSetting(String::from(
+            "icon URL must start with https://",
+        )));
+    };
+
+    if rest.is_empty() || rest.starts_with('/') {
+        return Err(EasyTotpError::InvalidSetting(String::from(
+            "icon URL has no host",
+        )));
+    }
+
+    if url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(EasyTotpError::InvalidSetting(String::from(
+            "icon URL must not contain whitespace or control characters",
+        )));
+    }
+
+    Ok(())
+}
+
+/// Reads the `image` parameter of an otpauth URI, ignoring it unless it is a valid icon URL, since it is purely cosmetic
+#[cfg(feature = "std")]
+fn icon_url_param(uri: &str) -> Option<String> {
+    let (_, query) = uri.split_once('?')?;
+    let encoded = query
+        .split('&')
+        .find_map(|param| param.strip_prefix("image="))?;
+    let url = urlencoding::decode(encoded).ok()?.into_owned();
+
+    validate_icon_url(&url).ok().map(|()| url)
+}
+
+/// Serde default for `EasyTotp::quiet_zone`, so that data serialized before it existed keeps the standard border
+#[cfg(feature = "plaintext-serde")]
+fn default_quiet_zone() -> u8 {
+    DEFAULT_QUIET_ZONE
+}
+
+/// Serde default for `EasyTotp::min_secret_len`, so that data serialized before it existed keeps the RFC 4226 minimum
+#[cfg(feature = "plaintext-serde")]
+fn default_min_secret_len() -> usize {
+    MIN_SECRET_LEN
+}
+
+/// The current Unix timestamp, in seconds
+#[cfg(feature = "std")]
+fn unix_time() -> Result<u64, EasyTotpError> {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(duration) => Ok(duration.as_secs()),
+        Err(e) => Err(EasyTotpError::SystemTime(e)),
+    }
+}
+
+/// Generates a random secret key of `len` bytes from `OsRng`
+#[cfg(feature = "std")]
+fn random_secret(len: usize) -> Result<Zeroizing<Vec<u8>>, EasyTotpError> {
+    random_secret_from(&mut OsRng, len)
+}
+
+/// Draws a secret key of `len` bytes from `rng`
+#[cfg(feature = "std")]
+fn random_secret_from<R>(rng: &mut R, len: usize) -> Result<Zeroizing<Vec<u8>>, EasyTotpError>
+where
+    R: TryCryptoRng + ?Sized,
+    R::Error: Error + Send + Sync + 'static,
+{
+    let mut secret_bytes = Zeroizing::new(vec![0u8; len]);
+    if let Err(e) = rng.try_fill_bytes(secret_bytes.as_mut()) {
+        return Err(EasyTotpError::Rng(Box::new(e)));
+    }
+
+    Ok(secret_bytes)
+}
+
+/// Decodes a base32 secret key, accepting only `A`–`Z` and `2`–`7` with optional `=` padding
+#[cfg(feature = "std")]
+fn decode_base32_secret(secret: &str) -> Result<Zeroizing<Vec<u8>>, EasyTotpError> {
+    // Authenticator apps display secrets in lowercase groups of four, e.g. `jbsw y3dp ehpk 3pxp`
+    let normalized: Zeroizing<String> = Zeroizing::new(
+        secret
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| c.to_ascii_uppercase())
+            .collect(),
+    );
+    let unpadded = normalized.trim_end_matches('=');
+
+    if unpadded.is_empty() {
+        return Err(EasyTotpError::SecretParse(String::from(
+            "secret key is empty",
+        )));
+    }
+
+    if let Some(invalid) = unpadded
+        .chars()
+        .find(|c| !(c.is_ascii_uppercase() || ('2'..='7').contains(c)))
+    {
+        return Err(EasyTotpError::SecretParse(format!(
+            "secret key contains invalid base32 character {invalid:?}"
+        )));
+    }
+
+    match Secret::Encoded(unpadded.to_string()).to_bytes() {
+        Ok(raw_secret) => Ok(Zeroizing::new(raw_secret)),
+        Err(_) => Err(EasyTotpError::SecretParse(String::from(
+            "failed to decode base32 secret key",
+        ))),
+    }
+}
+
+/// Relative luminance of an sRGB color, from 0 (black) to 1 (white), as defined by WCAG 2
+#[cfg(feature = "std")]
+fn relative_luminance(color: [u8; 3]) -> f64 {
+    let [red, green, blue] = color.map(|channel| {
+        let channel = f64::from(channel) / 255.0;
+        if channel <= 0.040_45 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    });
+
+    0.2126 * red + 0.7152 * green + 0.0722 * blue
+}
+
+/// The width of a QR code of the given version, in modules
+#[cfg(feature = "std")]
+fn qr_modules(version: u8) -> u32 {
+    17 + 4 * u32::from(version)
+}
+
+/// Encodes an otpauth URI into a QR code matrix
+#[cfg(feature = "std")]
+fn encode_qr(uri: &str, ecc: QREccLevel) -> Result<QrCode, EasyTotpError> {
+    match QrCode::encode_text(uri, ecc.to_qrcodegen()) {
+        Ok(qr) => Ok(qr),
+        Err(e) => Err(EasyTotpError::QrGeneration(e.to_string())),
+    }
+}
+
+/// Encodes an otpauth URI as a grid of QR modules, indexed `[y][x]`, where `true` is dark; every renderer draws from this
+#[cfg(feature = "std")]
+fn module_matrix(uri: &str, ecc: QREccLevel) -> Result<Vec<Vec<bool>>, EasyTotpError> {
+    let qr = encode_qr(uri, ecc)?;
+
+    Ok((0..qr.size())
+        .map(|y| (0..qr.size()).map(|x| qr.get_module(x, y)).collect())
+        .collect())
+}
+
+/// Whether the module at `(x, y)` is dark, counting from the outer edge of a quiet zone of `quiet_zone` light modules
+#[cfg(feature = "std")]
+fn padded_module(matrix: &[Vec<bool>], quiet_zone: usize, x: usize, y: usize) -> bool {
+    match (x.checked_sub(quiet_zone), y.checked_sub(quiet_zone)) {
+        (Some(x), Some(y)) => matrix
+            .get(y)
+            .and_then(|row| row.get(x))
+            .copied()
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Renders an otpauth URI as QR code text for terminal display, without any instructions
+#[cfg(feature = "std")]
+fn qr_text_lines(
+    uri: &str,
+    options: QrOptions,
+    size: QRSize,
+    mode: QRColorMode,
+    target_width: u32,
+    ambiguous_width: QRAmbiguousWidth,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    if target_width == 0 {
+        return Err(Box::new(EasyTotpError::InvalidSetting(String::from(
+            "target width must be greater than zero",
+        ))));
+    }
+
+    let img = render_qr_image(uri, options, 8)?;
+
+    let mut lines = luma_to_block_lines(&img, target_width, options.shading);
+
+    stdout().flush()?;
+
+    match mode {
+        QRColorMode::Direct => {}
+        QRColorMode::Inverted => {
+            for line in &mut lines {
+                *line = invert_block_line(line);
+            }
+        }
+    }
+
+    if size == QRSize::Mini {
+        lines = lines
+            .chunks(2)
+            .map(|pair| {
+                let mut mini_line = String::new();
+                for (c1, c2) in pair[0]
+                    .chars()
+                    .zip(pair.get(1).unwrap_or(&String::new()).chars())
+                {
+                    let mini_char = match (c1, c2) {
+                        ('█' | '▓' | '▒', ' ') => '▀',
+                        (' ', '█' | '▓' | '▒') => '▄',
+                        ('█', '█') => '█',
+                        ('▓', '▓') => '▓',
+                        ('▒', '▒') => '▒',
+                        _ => ' ',
+                    };
+                    mini_line.push(mini_char);
+                }
+                mini_line
+            })
+            .collect();
+    }
+
+    // Glyphs wider than one cell would stretch the QR code out of shape, so fall back to double-width ASCII cells
+    let single_width = lines
+        .iter()
+        .flat_map(|line| line.chars())
+        .all(|c| ambiguous_width.char_width(c) == Some(1));
+    if !single_width {
+        lines = qr_module_lines(uri, options, mode, "##", "  ")?;
+    }
+
+    Ok(lines)
+}
+
+/// Swaps dark and light block characters in a row of the terminal QR code
+#[cfg(feature = "std")]
+fn invert_block_line(line: &str) -> String {
+    line.chars()
+        .map(|c| match c {
+            '█' => ' ',
+            '▓' => '░',
+            '▒' => '▓',
+            ' ' => '█',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Renders an otpauth URI as one line of text per row of QR modules, including the quiet zone
+///
+/// Dark modules are drawn as `ink` and light modules as `paper` in direct mode, and the other way round in inverted mode.
+#[cfg(feature = "std")]
+fn qr_module_lines(
+    uri: &str,
+    options: QrOptions,
+    mode: QRColorMode,
+    ink: &str,
+    paper: &str,
+) -> Result<Vec<String>, EasyTotpError> {
+    let quiet_zone = usize::from(options.quiet_zone);
+
+    let matrix = module_matrix(uri, options.ecc)?;
+    let (dark, light) = match mode {
+        QRColorMode::Direct => (ink, paper),
+        QRColorMode::Inverted => (paper, ink),
+    };
+
+    let size = matrix.len() + quiet_zone * 2;
+    Ok((0..size)
+        .map(|y| {
+            (0..size)
+                .map(|x| {
+                    if padded_module(&matrix, quiet_zone, x, y) {
+                        dark
+                    } else {
+                        light
+                    }
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// The English instructions printed below terminal QR codes by default; `kind` names the scheme (e.g. `"TOTP"`)
+#[cfg(feature = "std")]
+fn qr_instructions(kind: &str) -> Vec<String> {
+    vec![
+        format!("Scan the above QR code with your authenticator app to set up {kind}."),
+        String::from("BEWARE: this QR code contains your secret key! Handle with care."),
+        String::from(
+            "Useful tips: if scanning fails, try inverting the QR code colors by adjusting your terminal's background color or ",
+        ),
+        String::from(
+            "using your mouse to select the entire QR code area. Also, ensure your terminal zoom is set to a level that allows ",
+        ),
+        String::from("the QR code to be completely visible onscreen."),
+    ]
+}
+
+/// Writes each line of a text rendering to `writer`, followed by a newline
+#[cfg(feature = "std")]
+fn write_lines<W: Write>(writer: &mut W, lines: &[String]) -> std::io::Result<()> {
+    for line in lines {
+        writeln!(writer, "{line}")?;
+    }
+
+    writer.flush()
+}
+
+/// The width of the terminal in columns, or 80 when it can't be detected (e.g. when output is piped)
+#[cfg(feature = "std")]
+fn terminal_width() -> u32 {
+    match terminal_size::terminal_size() {
+        Some((terminal_size::Width(columns), _)) if columns > 0 => u32::from(columns),
+        _ => 80,
+    }
+}
+
+/// Samples a grayscale image into lines of block characters, at most `terminal_width` characters wide
+#[cfg(feature = "std")]
+fn luma_to_block_lines(img: &GrayImage, terminal_width: u32, shading: QRShading) -> Vec<String> {
+    let mut lines = Vec::new();
+    let thresholds = shading.thresholds();
+
+    let width = img.width();
+    let height = img.height();
+
+    // Determine scaling factor to fit terminal, rounding up so that lines never wrap
+    // Clamp to 1 so that images narrower than the terminal don't hit `step_by(0)`
+    let scale_x = width.div_ceil(terminal_width).max(1);
+    let scale_y = (scale_x * 2).max(1); // Height is doubled for character aspect ratio
+
+    for y in (0..height).step_by(scale_y as usize) {
+        let mut line = String::new();
+        for x in (0..width).step_by(scale_x as usize) {
+            // Sample the block of pixels and determine overall darkness
+            let block_darkness = (0..scale_x)
+                .flat_map(|dx| {
+                    (0..scale_y).map(move |dy| {
+                        let px = (x + dx).min(width - 1);
+                        let py = (y + dy).min(height - 1);
+                        img.get_pixel(px, py)[0]
+                    })
+                })
+                .filter(|&p| p < 128)
+                .count();
+
+            // Compare percentages in integers, so that e.g. exactly half dark is never rounded above 50%
+            let total_pixels = (scale_x * scale_y) as usize;
+            let exceeds = |percent: u8| block_darkness * 100 > usize::from(percent) * total_pixels;
+            let symbol = if exceeds(thresholds.dark) {
+                '█' // Very dark
+            } else if exceeds(thresholds.medium) {
+                '▓' // Medium-dark
+            } else if exceeds(thresholds.light) {
+                '▒' // Light
+            } else {
+                ' ' // Very light
+            };
+
+            line.push(symbol);
+        }
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Renders the QR code of `uri` into a grayscale image at `module_px` pixels per module, surrounded by the quiet zone
+#[cfg(feature = "std")]
+fn render_qr_image(
+    uri: &str,
+    options: QrOptions,
+    module_px: u32,
+) -> Result<GrayImage, EasyTotpError> {
+    render_matrix_image(
+        &module_matrix(uri, options.ecc)?,
+        options.quiet_zone,
+        module_px,
+    )
+}
+
+/// Renders a QR matrix into a grayscale image at `module_px` pixels per module, surrounded by the quiet zone
+#[cfg(feature = "std")]
+fn render_matrix_image(
+    matrix: &[Vec<bool>],
+    quiet_zone_modules: u8,
+    module_px: u32,
+) -> Result<GrayImage, EasyTotpError> {
+    if module_px == 0 {
+        return Err(EasyTotpError::InvalidSetting(String::from(
+            "module size must be at least 1 pixel",
+        )));
+    }
+
+    let quiet_zone = u32::from(quiet_zone_modules);
+    // QR codes are at most 177 modules wide, so this always fits
+    let modules = u32::try_from(matrix.len()).unwrap_or(u32::MAX / 4);
+    let Some(image_size) = (modules + quiet_zone * 2)
+        .checked_mul(module_px)
+        .filter(|&size| size <= MAX_IMAGE_SIZE)
+    else {
+        return Err(EasyTotpError::InvalidSetting(format!(
+            "module size of {module_px} pixels is too large"
+        )));
+    };
+
+    Ok(GrayImage::from_fn(image_size, image_size, |px, py| {
+        let dark = padded_module(
+            matrix,
+            usize::from(quiet_zone_modules),
+            (px / module_px) as usize,
+            (py / module_px) as usize,
+        );
+
+        image::Luma([if dark { 0 } else { 255 }])
+    }))
+}
+
+/// Renders an otpauth URI as an SVG document
+#[cfg(feature = "std")]
+fn qr_svg(uri: &str, options: QrOptions) -> Result<String, EasyTotpError> {
+    let quiet_zone = usize::from(options.quiet_zone);
+
+    let matrix = module_matrix(uri, options.ecc)?;
+    let dimension = matrix.len() + quiet_zone * 2;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" viewBox=\"0 0 {dimension} {dimension}\" shape-rendering=\"crispEdges\">\n"
+    );
+    svg.push_str("<rect width=\"100%\" height=\"100%\" fill=\"#FFFFFF\"/>\n");
+
+    for (y, row) in matrix.iter().enumerate() {
+        for (x, &dark) in row.iter().enumerate() {
+            if dark {
+                // Writing to a `String` cannot fail
+                let _ = writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\" fill=\"#000000\"/>",
+                    x + quiet_zone,
+                    y + quiet_zone
+                );
+            }
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::fs;
+    use std::io::Write;
+
+    /// An account with the secret of the RFC 6238 SHA1 test vectors, and otherwise default settings
+    fn rfc_account() -> EasyTotp {
+        EasyTotp::from_base32_secret(
+            "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ",
+            Some(String::from("McCormick")),
+            String::from("test@test-email.com"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_qr_png() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let filename = "./test_images/qr_code.png";
+
+        let et = EasyTotp::new(issuer, account_name).unwrap();
+
+        let my_qr_code = et.create_qr_png();
+
+        match my_qr_code {
+            Ok(png_data) => {
+                let mut file = fs::File::create(filename).unwrap();
+                file.write_all(&png_data).unwrap();
+                println!("QR code saved as 'qr_code.png'");
+            }
+            Err(e) => {
+                panic!("Error creating QR code: {e:?}");
+            }
+        }
+
+        let img = image::open(filename).unwrap().to_luma8();
+        // Prepare for detection
+        let mut img = rqrr::PreparedImage::prepare(img);
+        // Search for grids, without decoding
+        let grids = img.detect_grids();
+        assert_eq!(grids.len(), 1);
+        // Decode the grid
+        let (meta, content) = grids[0].decode().unwrap();
+        assert_eq!(meta.ecc_level, 0);
+        assert!(content.starts_with("otpauth://totp/McCormick:test%40test-email.com?secret="));
+        assert!(content.contains("&algorithm=SHA512&issuer=McCormick"));
+
+        // Extract and validate the secret parameter
+        let secret_start = content.find("secret=").unwrap() + 7;
+        let secret_end = content.find("&algorithm=").unwrap();
+        let secret = &content[secret_start..secret_end];
+
+        // Ensure the secret is not empty and has reasonable length
+        assert!(!secret.is_empty());
+        assert!(
+            secret.len() >= 20,
+            "Secret should be at least 20 characters long"
+        );
+
+        // Verify the secret only contains valid base32 characters (A-Z, 2-7)
+        assert!(
+            secret
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || "234567".contains(c)),
+            "Secret should only contain valid base32 characters"
+        );
+
+        // Delete file
+        fs::remove_file(filename).unwrap();
+    }
+
+    #[test]
+    fn test_qr_terminal() {
+        let raw_secret = Zeroizing::new(b"SUPERSecretSecretSecret".to_vec());
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("Account_name");
+
+        let et = EasyTotp {
+            raw_secret,
+            issuer,
+            account_name,
+            ..Default::default()
+        };
+
+        match et.print_qr_to_terminal(QRColorMode::Direct) {
+            Ok(()) => println!("QR code rendered in terminal successfully."),
+            Err(e) => panic!("Error rendering QR code in terminal: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_code_generation() {
+        let raw_secret = Zeroizing::new(b"SUPERSecretSecretSecret".to_vec());
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let ets: Vec<EasyTotp> = [6, 7, 8]
+            .into_iter()
+            .map(|digits| {
+                EasyTotp {
+                    raw_secret: raw_secret.clone(),
+                    issuer: issuer.clone(),
+                    account_name: account_name.clone(),
+                    ..Default::default()
+                }
+                .with_digits(digits)
+                .unwrap()
+            })
+            .collect();
+
+        for et in &ets {
+            // Both timestamps fall within the same 30-second step, the third one is in the next step
+            let token1 = et.generate_token_at(1_700_000_010).unwrap();
+            let token2 = et.generate_token_at(1_700_000_019).unwrap();
+            assert_eq!(token1, token2);
+
+            let token3 = et.generate_token_at(1_700_000_040).unwrap();
+            assert_ne!(token1, token3);
+
+            let digits = et.digits;
+            assert_eq!(
+                (digits, digits, digits),
+                (token1.len(), token2.len(), token3.len())
+            );
+
+            assert_eq!(
+                (true, true, true),
+                (
+                    token1.parse::<u32>().is_ok(),
+                    token2.parse::<u32>().is_ok(),
+                    token3.parse::<u32>().is_ok(),
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_token_verification() {
+        let raw_secret = Zeroizing::new(b"SUPERSecretSecretSecret".to_vec());
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp {
+            raw_secret,
+            issuer,
+            account_name,
+            ..Default::default()
+        };
+
+        let token = et.generate_token().unwrap();
+        assert!(et.verify_token(&token).unwrap());
+
+        let wrong_token = format!("{:06}", (token.parse::<u32>().unwrap() + 1) % 1_000_000);
+        assert!(!et.verify_token(&wrong_token).unwrap());
+
+        for garbage in ["", "12345", "1234567", "abcdef", "12 456", "１２３４５６"] {
+            assert!(!et.verify_token(garbage).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_algorithm_selection() {
+        let raw_secret = Zeroizing::new(b"SUPERSecretSecretSecret".to_vec());
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp {
+            raw_secret,
+            issuer,
+            account_name,
+            ..Default::default()
+        };
+
+        let sha512_totp = et.new_totp().unwrap();
+        assert_eq!(sha512_totp.algorithm, Algorithm::SHA512);
+        assert!(sha512_totp.get_url().contains("algorithm=SHA512"));
+
+        let et = et.with_algorithm(Algorithm::SHA256);
+        let sha256_totp = et.new_totp().unwrap();
+        assert_eq!(sha256_totp.algorithm, Algorithm::SHA256);
+        assert!(sha256_totp.get_url().contains("algorithm=SHA256"));
+        assert_ne!(sha512_totp.generate(59), sha256_totp.generate(59));
+
+        // SHA1 is the otpauth default, so the parameter is omitted from the URI
+        let et = et.with_algorithm(Algorithm::SHA1);
+        assert!(!et.new_totp().unwrap().get_url().contains("algorithm="));
+    }
+
+    #[test]
+    fn test_digits_validation() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name).unwrap();
+
+        for digits in [0, 5, 9] {
+            assert!(et.clone().with_digits(digits).is_err());
+        }
+
+        let et = et.with_digits(8).unwrap();
+        assert!(et.new_totp().unwrap().get_url().contains("digits=8"));
+
+        let token = et.generate_token().unwrap();
+        assert!(et.verify_token(&token).unwrap());
+        assert!(!et.verify_token(&token[..6]).unwrap());
+    }
+
+    #[test]
+    fn test_period_validation() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name).unwrap();
+
+        assert!(et.clone().with_period(0).is_err());
+
+        let et = et.with_period(60).unwrap();
+        let totp = et.new_totp().unwrap();
+        assert!(totp.get_url().contains("&period=60"));
+
+        // Both timestamps fall within the same 60-second step
+        assert_eq!(totp.generate(60), totp.generate(119));
+        assert_ne!(totp.generate(60), totp.generate(120));
+    }
+
+    #[test]
+    fn test_skew_window() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name).unwrap();
+
+        let now = 1_000_000_000;
+        let totp = et.new_totp().unwrap();
+        let two_steps_ago = totp.generate(now - 60);
+        assert!(!totp.check(&two_steps_ago, now));
+
+        let totp = et.with_skew(2).new_totp().unwrap();
+        assert!(totp.check(&two_steps_ago, now));
+    }
+
+    #[test]
+    fn test_from_base32_secret() {
+        // RFC 6238 Appendix B test secret: ASCII "12345678901234567890"
+        let secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+
+        let et = EasyTotp::from_base32_secret(secret, issuer.clone(), account_name.clone())
+            .unwrap()
+            .with_algorithm(Algorithm::SHA1)
+            .with_digits(8)
+            .unwrap();
+        assert_eq!(et.raw_secret.as_slice(), b"12345678901234567890");
+
+        let totp = et.new_totp().unwrap();
+        assert_eq!(totp.generate(59), "94287082");
+        assert_eq!(totp.generate(1_111_111_109), "07081804");
+
+        let padded =
+            EasyTotp::from_base32_secret("GEZDGNBV====", issuer.clone(), account_name.clone());
+        assert_eq!(padded.unwrap().raw_secret.as_slice(), b"12345");
+
+        for invalid in [
+            "",
+            "====",
+            "   ",
+            "GEZDGNBV1",
+            "GEZD=GNBV",
+            "GEZDGNB!",
+            "GEZD-GNBV",
+        ] {
+            assert!(
+                EasyTotp::from_base32_secret(invalid, issuer.clone(), account_name.clone())
+                    .is_err(),
+                "{invalid:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_base32_secret_normalization() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let import = |secret: &str| {
+            EasyTotp::from_base32_secret(secret, issuer.clone(), account_name.clone())
+                .unwrap()
+                .raw_secret
+        };
+
+        let expected = import("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+        assert_eq!(expected.as_slice(), b"12345678901234567890");
+        for secret in [
+            // Lowercase
+            "gezdgnbvgy3tqojqgezdgnbvgy3tqojq",
+            // Grouped in fours, as authenticator apps display it
+            "GEZD GNBV GY3T QOJQ GEZD GNBV GY3T QOJQ",
+            "gezd gnbv gy3t qojq gezd gnbv gy3t qojq",
+            // Stray whitespace from copying and pasting
+            "  GEZDGNBVGY3TQOJQ\tGEZDGNBVGY3TQOJQ\n",
+            // Mixed case
+            "GezdGnbvGy3tQojqGezdGnbvGy3tQojq",
+        ] {
+            assert_eq!(import(secret), expected, "{secret:?}");
+        }
+
+        // With and without padding
+        let unpadded = import("jbsw y3dp ehpk 3pxp");
+        assert_eq!(unpadded, import("JBSWY3DPEHPK3PXP"));
+        assert_eq!(import("gezd gnbv ===="), import("GEZDGNBV"));
+        assert_eq!(import("GEZDGNBV====").as_slice(), b"12345");
+
+        let eh = EasyHotp::from_base32_secret("jbsw y3dp ehpk 3pxp", None, String::from("x"));
+        assert_eq!(eh.unwrap().secret_base32(), "JBSWY3DPEHPK3PXP");
+    }
+
+    #[test]
+    fn test_blank_names() {
+        let account_name = String::from("test@test-email.com");
+        let blank = ["", " ", "\t\n"];
+
+        for name in blank {
+            for result in [
+                EasyTotp::new(None, String::from(name)),
+                EasyTotp::new(Some(String::from("McCormick")), String::from(name)),
+                EasyTotp::new(Some(String::from(name)), account_name.clone()),
+                EasyTotp::new_checked(Some(String::from(name)), account_name.clone()),
+                EasyTotp::new_with_secret_length(None, String::from(name), 32),
+                EasyTotp::builder().account_name(name).build(),
+                EasyTotp::builder()
+                    .issuer(name)
+                    .account_name(account_name.clone())
+                    .build(),
+            ] {
+                assert!(matches!(result, Err(EasyTotpError::InvalidSetting(_))));
+            }
+            assert!(matches!(
+                EasyHotp::new(None, String::from(name)),
+                Err(EasyTotpError::InvalidSetting(_))
+            ));
+        }
+
+        // No issuer at all is fine, and names are not trimmed
+        let et = EasyTotp::new(None, String::from(" test ")).unwrap();
+        assert_eq!(et.account_name, " test ");
+        assert!(EasyHotp::new(Some(String::from("McCormick")), account_name).is_ok());
+
+        // Existing credentials with blank names can still be imported and used, as long as the URI has an account name at all
+        let et = EasyTotp::from_otpauth_uri(
+            "otpauth://totp/%20?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&algorithm=SHA1&digits=8",
+        )
+        .unwrap();
+        assert_eq!(et.account_name, " ");
+        assert_eq!(et.generate_token_at(59).unwrap(), "94287082");
+        let et =
+            EasyTotp::from_base32_secret("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ", None, String::new())
+                .unwrap();
+        assert!(et.generate_token_at(59).is_ok());
+    }
+
+    #[test]
+    fn test_new_from_rng() {
+        use rand::{CryptoRng, RngCore, SeedableRng, TryRngCore, rngs::StdRng};
+
+        /// Fills every byte with the same value. Not actually secure, but fine for testing.
+        struct ConstRng(u8);
+
+        impl RngCore for ConstRng {
+            fn next_u32(&mut self) -> u32 {
+                u32::from_ne_bytes([self.0; 4])
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                u64::from_ne_bytes([self.0; 8])
+            }
+
+            fn fill_bytes(&mut self, dst: &mut [u8]) {
+                dst.fill(self.0);
+            }
+        }
+
+        impl CryptoRng for ConstRng {}
+
+        /// Always fails, like a hardware RNG that has gone offline
+        struct FailingRng;
+
+        impl TryRngCore for FailingRng {
+            type Error = std::io::Error;
+
+            fn try_next_u32(&mut self) -> Result<u32, Self::Error> {
+                Err(std::io::Error::other("RNG offline"))
+            }
+
+            fn try_next_u64(&mut self) -> Result<u64, Self::Error> {
+                Err(std::io::Error::other("RNG offline"))
+            }
+
+            fn try_fill_bytes(&mut self, _dst: &mut [u8]) -> Result<(), Self::Error> {
+                Err(std::io::Error::other("RNG offline"))
+            }
+        }
+
+        impl TryCryptoRng for FailingRng {}
+
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+
+        let et =
+            EasyTotp::new_from_rng(&mut ConstRng(0), issuer.clone(), account_name.clone()).unwrap();
+        assert_eq!(et.secret_base32(), "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA");
+        assert_eq!(et.issuer, issuer);
+        assert_eq!(et.account_name, account_name);
+        assert_eq!(et.secret_entropy_bits(), 160);
+        assert_eq!(
+            (et.algorithm, et.digits, et.period),
+            (
+                EasyTotp::DEFAULT_ALGORITHM,
+                EasyTotp::DEFAULT_DIGITS,
+                EasyTotp::DEFAULT_PERIOD
+            )
+        );
+
+        // Seeded RNGs give the same credential, and so the same tokens, every time
+        let seeded = |seed| {
+            EasyTotp::new_from_rng(&mut StdRng::seed_from_u64(seed), None, account_name.clone())
+                .unwrap()
+        };
+        let (et1, et2, et3) = (seeded(7), seeded(7), seeded(8));
+        assert_eq!(et1, et2);
+        assert_eq!(
+            et1.generate_token_at(1_700_000_000).unwrap(),
+            et2.generate_token_at(1_700_000_000).unwrap()
+        );
+        assert_ne!(et1.secret_base32(), et3.secret_base32());
+
+        // Works with `OsRng` and trait objects too
+        let et = EasyTotp::new_from_rng(&mut OsRng, None, account_name.clone()).unwrap();
+        assert_eq!(et.secret_entropy_bits(), 160);
+        let rng: &mut dyn CryptoRng = &mut ConstRng(0xff);
+        let et = EasyTotp::new_from_rng(rng, None, account_name.clone()).unwrap();
+        assert_eq!(et.secret_base32(), "77777777777777777777777777777777");
+
+        assert!(matches!(
+            EasyTotp::new_from_rng(&mut FailingRng, None, account_name),
+            Err(EasyTotpError::Rng(e)) if e.to_string() == "RNG offline"
+        ));
+        assert!(matches!(
+            EasyTotp::new_from_rng(&mut ConstRng(0), None, String::new()),
+            Err(EasyTotpError::InvalidSetting(_))
+        ));
+    }
+
+    #[test]
+    fn test_new_with_retry() {
+        use rand::TryRngCore;
+        use std::time::Instant;
+
+        /// Fails the first `failures` times, like an RNG that isn't ready yet, then fills every byte with 1
+        struct FlakyRng {
+            failures: u32,
+            calls: u32,
+        }
+
+        impl TryRngCore for FlakyRng {
+            type Error = std::io::Error;
+
+            fn try_next_u32(&mut self) -> Result<u32, Self::Error> {
+                Err(std::io::Error::other("only `try_fill_bytes` is used"))
+            }
+
+            fn try_next_u64(&mut self) -> Result<u64, Self::Error> {
+                Err(std::io::Error::other("only `try_fill_bytes` is used"))
+            }
+
+            fn try_fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), Self::Error> {
+                self.calls += 1;
+                if self.calls <= self.failures {
+                    return Err(std::io::Error::other(format!(
+                        "RNG not ready ({})",
+                        self.calls
+                    )));
+                }
+
+                dst.fill(1);
+                Ok(())
+            }
+        }
+
+        impl TryCryptoRng for FlakyRng {}
+
+        let account_name = String::from("test@test-email.com");
+        let policy = RngRetryPolicy {
+            attempts: 3,
+            delay: Duration::from_millis(5),
+        };
+
+        // Recovers within the attempts
+        let mut rng = FlakyRng {
+            failures: 2,
+            calls: 0,
+        };
+        let start = Instant::now();
+        let et = EasyTotp::new_from_rng_with_retry(&mut rng, None, account_name.clone(), policy)
+            .unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(10));
+        assert_eq!(rng.calls, 3);
+        assert_eq!(et.raw_secret.as_slice(), &[1; DEFAULT_SECRET_LEN]);
+
+        // Gives up with a typed error holding the last cause, never a weaker secret
+        let mut rng = FlakyRng {
+            failures: 3,
+            calls: 0,
+        };
+        let result =
+            EasyTotp::new_from_rng_with_retry(&mut rng, None, account_name.clone(), policy);
+        assert!(matches!(
+            result,
+            Err(EasyTotpError::Rng(e)) if e.to_string() == "RNG not ready (3)"
+        ));
+        assert_eq!(rng.calls, 3);
+
+        // A single attempt behaves like `new_from_rng`
+        let single = RngRetryPolicy {
+            attempts: 1,
+            ..policy
+        };
+        let mut rng = FlakyRng {
+            failures: 1,
+            calls: 0,
+        };
+        assert!(
+            EasyTotp::new_from_rng_with_retry(&mut rng, None, account_name.clone(), single)
+                .is_err()
+        );
+        assert_eq!(rng.calls, 1);
+
+        // Invalid settings are rejected before the RNG is touched
+        for (account_name, policy) in [
+            (String::new(), policy),
+            (
+                account_name.clone(),
+                RngRetryPolicy {
+                    attempts: 0,
+                    ..policy
+                },
+            ),
+        ] {
+            let mut rng = FlakyRng {
+                failures: 0,
+                calls: 0,
+            };
+            assert!(matches!(
+                EasyTotp::new_from_rng_with_retry(&mut rng, None, account_name, policy),
+                Err(EasyTotpError::InvalidSetting(_))
+            ));
+            assert_eq!(rng.calls, 0);
+        }
+
+        let et = EasyTotp::new_with_retry(None, account_name, RngRetryPolicy::default()).unwrap();
+        assert_eq!(et.secret_entropy_bits(), 160);
+    }
+
+    #[test]
+    fn test_manual_entry_details() {
+        let et = rfc_account()
+            .with_algorithm(Algorithm::SHA1)
+            .with_digits(8)
+            .unwrap()
+            .with_period(60)
+            .unwrap();
+
+        let lines: Vec<String> = et
+            .manual_entry_details()
+            .lines()
+            .map(String::from)
+            .collect();
+        assert!(lines[0].starts_with("BEWARE"));
+        assert_eq!(
+            lines[1..],
+            [
+                "Issuer:     McCormick",
+                "Account:    test@test-email.com",
+                "Secret key: GEZD GNBV GY3T QOJQ GEZD GNBV GY3T QOJQ",
+                "Type:       Time-based (TOTP)",
+                "Algorithm:  SHA1",
+                "Digits:     8",
+                "Period:     60 seconds",
+            ]
+        );
+
+        // The grouped secret still decodes to the same key
+        let grouped = lines[3].trim_start_matches("Secret key: ");
+        let reimported = EasyTotp::from_base32_secret(grouped, None, String::new()).unwrap();
+        assert_eq!(reimported.secret_base32(), et.secret_base32());
+
+        let et =
+            EasyTotp::from_base32_secret("JBSWY3DPEHPK3PXPJBSWY3DPEE", None, String::from("x"))
+                .unwrap()
+                .with_algorithm(Algorithm::Steam);
+        let details = et.manual_entry_details();
+        assert!(details.contains("Issuer:     (none)"));
+        assert!(details.contains("Secret key: JBSW Y3DP EHPK 3PXP JBSW Y3DP EE\n"));
+        assert!(details.contains("Algorithm:  Steam"));
+        assert!(details.contains("Digits:     5"));
+    }
+
+    #[test]
+    fn test_secret_base32_matches_qr() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name).unwrap();
+
+        let secret = et.secret_base32();
+        assert!(
+            et.new_totp()
+                .unwrap()
+                .get_url()
+                .contains(&format!("secret={secret}&"))
+        );
+
+        let reimported = EasyTotp::from_base32_secret(&secret, None, String::from("x")).unwrap();
+        assert_eq!(reimported.raw_secret, et.raw_secret);
+    }
+
+    #[test]
+    fn test_to_otpauth_uri() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name).unwrap();
+
+        let uri = et.to_otpauth_uri().unwrap();
+        assert!(uri.starts_with("otpauth://totp/"));
+        assert!(uri.contains("McCormick"));
+        assert!(uri.contains("issuer=McCormick"));
+        assert!(uri.contains("test%40test-email.com"));
+        assert!(uri.contains(&format!("secret={}", et.secret_base32())));
+    }
+
+    #[test]
+    fn test_unicode_labels() {
+        let labels = [
+            // CJK, percent-encoded byte by byte
+            (
+                "株式会社",
+                "ユーザー@例え.jp",
+                "%E6%A0%AA%E5%BC%8F%E4%BC%9A%E7%A4%BE",
+            ),
+            // Emoji outside the Basic Multilingual Plane take four bytes
+            ("🔐 Vault", "test@test-email.com", "%F0%9F%94%90%20Vault"),
+            // Combining characters and right-to-left scripts are kept exactly as given
+            ("Cafe\u{301}", "مستخدم", "Cafe%CC%81"),
+            ("McCormick", "用户 👩‍💻", "McCormick"),
+        ];
+
+        for (issuer, account_name, encoded_issuer) in labels {
+            let et = EasyTotp::new(Some(String::from(issuer)), String::from(account_name))
+                .unwrap()
+                .with_algorithm(Algorithm::SHA1);
+            let uri = et.to_otpauth_uri().unwrap();
+
+            // Scanners expect pure ASCII, with every byte of multi-byte UTF-8 percent-encoded
+            assert!(uri.is_ascii(), "{uri}");
+            assert!(uri.starts_with(&format!(
+                "otpauth://totp/{encoded_issuer}:{}?",
+                urlencoding::encode(account_name)
+            )));
+            assert!(uri.contains(&format!("&issuer={encoded_issuer}")));
+
+            let imported = EasyTotp::from_otpauth_uri(&uri).unwrap();
+            assert_eq!(imported, et);
+            assert_eq!(imported.issuer.as_deref(), Some(issuer));
+            assert_eq!(imported.account_name, account_name);
+
+            // The QR code carries the same URI
+            let mut decoder =
+                rqrr::PreparedImage::prepare(et.create_qr_image_buffer().unwrap().to_luma8());
+            let (_, content) = decoder.detect_grids()[0].decode().unwrap();
+            assert_eq!(content, uri);
+
+            let eh = EasyHotp::from_base32_secret(
+                &et.secret_base32(),
+                Some(String::from(issuer)),
+                String::from(account_name),
+            )
+            .unwrap();
+            let hotp_uri = eh.to_otpauth_uri().unwrap();
+            assert!(hotp_uri.is_ascii());
+            assert!(hotp_uri.starts_with(&format!("otpauth://hotp/{encoded_issuer}:")));
+
+            let migrated = import_migration_uri(&export_migration_uri(&[&et]).unwrap()).unwrap();
+            assert_eq!(migrated[0].issuer.as_deref(), Some(issuer));
+            assert_eq!(migrated[0].account_name, account_name);
+        }
+    }
+
+    #[test]
+    fn test_from_otpauth_uri() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name)
+            .unwrap()
+            .with_algorithm(Algorithm::SHA256)
+            .with_digits(8)
+            .unwrap()
+            .with_period(60)
+            .unwrap();
+
+        let round_tripped = EasyTotp::from_otpauth_uri(&et.to_otpauth_uri().unwrap()).unwrap();
+        assert_eq!(et, round_tripped);
+
+        for invalid in [
+            "",
+            "https://example.com",
+            "otpauth://hotp/McCormick:test?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=0",
+            "otpauth://totp/McCormick:test?issuer=McCormick",
+            "otpauth://totp/McCormick:test?secret=not-base32!",
+            "otpauth://totp/McCormick:test?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&digits=9",
+            "otpauth://totp/McCormick:test?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&period=0",
+            "otpauth://totp/McCormick:test?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&algorithm=MD5",
+        ] {
+            assert!(
+                EasyTotp::from_otpauth_uri(invalid).is_err(),
+                "{invalid:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_reuse_after_borrowing_methods() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name).unwrap();
+
+        // None of these consume `et`, so one instance can serve a whole login loop
+        let png = et.create_qr_png().unwrap();
+        let lines = et.qr_text(QRSize::Full, QRColorMode::Direct).unwrap();
+        for _ in 0..3 {
+            let token = et.generate_token().unwrap();
+            assert!(et.verify_token(&token).unwrap());
+        }
+
+        assert!(!png.is_empty());
+        assert!(!lines.is_empty());
+        assert_eq!(et.create_qr_png().unwrap(), png);
+    }
+
+    #[test]
+    fn test_qr_terminal_mini() {
+        let raw_secret = Zeroizing::new(b"SUPERSecretSecretSecret".to_vec());
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("Account_name");
+
+        let et = EasyTotp {
+            raw_secret,
+            issuer,
+            account_name,
+            ..Default::default()
+        };
+
+        let instructions = qr_instructions("TOTP");
+        let full = et.qr_text(QRSize::Full, QRColorMode::Direct).unwrap();
+        let mini = et.qr_text(QRSize::Mini, QRColorMode::Direct).unwrap();
+        let full_rows = full.len() - instructions.len();
+        assert_eq!(mini.len() - instructions.len(), full_rows.div_ceil(2));
+        // The instructions are kept as-is rather than squashed into half-blocks
+        assert!(mini.ends_with(&instructions));
+        assert!(
+            mini.iter()
+                .any(|line| line.contains('▀') || line.contains('▄'))
+        );
+
+        et.print_mini_qr_to_terminal(QRColorMode::Direct).unwrap();
+        et.print_mini_qr_to_terminal(QRColorMode::Inverted).unwrap();
+    }
+
+    #[test]
+    fn test_qr_terminal_sized() {
+        let raw_secret = Zeroizing::new(b"SUPERSecretSecretSecret".to_vec());
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("Account_name");
+
+        let et = EasyTotp {
+            raw_secret,
+            issuer,
+            account_name,
+            ..Default::default()
+        };
+
+        let narrow = et
+            .qr_text_sized(QRSize::Full, QRColorMode::Direct, 40)
+            .unwrap();
+        let wide = et
+            .qr_text_sized(QRSize::Full, QRColorMode::Direct, 100)
+            .unwrap();
+        assert!(narrow[0].chars().count() < wide[0].chars().count());
+        assert!(
+            narrow
+                .iter()
+                .take(10)
+                .all(|line| line.chars().count() <= 40)
+        );
+        assert!(wide.iter().take(10).all(|line| line.chars().count() <= 100));
+
+        // Wider than the QR image itself must not panic
+        for target_width in [1, 2, 10, 10_000, u32::MAX] {
+            et.qr_text_sized(QRSize::Full, QRColorMode::Direct, target_width)
+                .unwrap();
+        }
+
+        assert!(
+            et.qr_text_sized(QRSize::Full, QRColorMode::Direct, 0)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_block_lines_small_image() {
+        // A 10x10 image is far narrower than the requested terminal width, which used to panic on `step_by(0)`
+        let img = GrayImage::from_fn(10, 10, |x, _| image::Luma([if x < 5 { 0 } else { 255 }]));
+
+        let lines = luma_to_block_lines(&img, 100, QRShading::Grayscale);
+        assert_eq!(lines.len(), 5);
+        for line in &lines {
+            assert_eq!(line, &format!("{}{}", "█".repeat(5), " ".repeat(5)));
+        }
+
+        let lines = luma_to_block_lines(&img, 1, QRShading::Grayscale);
+        assert_eq!(lines, vec![String::from("▓")]);
+
+        // Binary shading snaps the half-dark block to light
+        let lines = luma_to_block_lines(&img, 1, QRShading::Binary);
+        assert_eq!(lines, vec![String::from(" ")]);
+
+        // Custom thresholds move it between shades
+        for (thresholds, expected) in [
+            ((49, 40, 20), "█"),
+            ((70, 50, 20), "▒"),
+            ((70, 50, 50), " "),
+            ((100, 100, 0), "▒"),
+            ((0, 0, 0), "█"),
+        ] {
+            let (dark, medium, light) = thresholds;
+            let shading = QRShading::Custom(QRThresholds {
+                dark,
+                medium,
+                light,
+            });
+            let lines = luma_to_block_lines(&img, 1, shading);
+            assert_eq!(lines, vec![String::from(expected)], "{thresholds:?}");
+        }
+    }
+
+    #[test]
+    fn test_totp_cache() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name).unwrap();
+        assert!(et.totp.get().is_none());
+
+        let token = et.generate_token().unwrap();
+        assert!(et.totp.get().is_some());
+        assert!(std::ptr::eq(et.totp().unwrap(), et.totp().unwrap()));
+        assert!(et.verify_token(&token).unwrap());
+
+        // Changing a setting must invalidate the cache
+        let et = et.with_digits(8).unwrap();
+        assert!(et.totp.get().is_none());
+        assert_eq!(et.generate_token().unwrap().len(), 8);
+        assert_eq!(et.totp().unwrap().digits, 8);
+
+        // Clones and comparisons are unaffected by the cache
+        let cold = EasyTotp {
+            totp: OnceLock::new(),
+            ..et.clone()
+        };
+        assert_eq!(et, cold);
+    }
+
+    #[test]
+    fn test_qr_svg() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name).unwrap();
+
+        let svg = et.create_qr_svg().unwrap();
+        let qr = encode_qr(&et.to_otpauth_uri().unwrap(), QREccLevel::Medium).unwrap();
+        let size = qr.size();
+        let dark_modules = (0..size)
+            .flat_map(|y| (0..size).map(move |x| (x, y)))
+            .filter(|&(x, y)| qr.get_module(x, y))
+            .count();
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.contains(&format!("viewBox=\"0 0 {0} {0}\"", size + 8)));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        // One background rect plus one rect per dark module
+        assert_eq!(svg.matches("<rect").count(), dark_modules + 1);
+        // The top-left finder pattern starts right after the quiet zone
+        assert!(svg.contains("<rect x=\"4\" y=\"4\" width=\"1\" height=\"1\""));
+    }
+
+    #[test]
+    fn test_qr_data_url() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name).unwrap();
+
+        let data_url = et.create_qr_data_url().unwrap();
+        let encoded = data_url.strip_prefix("data:image/png;base64,").unwrap();
+        let png = general_purpose::STANDARD.decode(encoded).unwrap();
+        assert_eq!(png, et.create_qr_png().unwrap());
+        assert_eq!(encoded, et.create_qr_base64().unwrap());
+
+        // Errors are typed, so web backends can match on them
+        let broken = EasyTotp {
+            account_name: String::from("test:test-email.com"),
+            raw_secret: et.raw_secret.clone(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            broken.create_qr_base64(),
+            Err(EasyTotpError::InvalidSetting(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_qr_png() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name).unwrap();
+
+        let mut sink = std::io::Cursor::new(Vec::new());
+        et.write_qr_png(&mut sink).unwrap();
+
+        let png = sink.into_inner();
+        assert!(png.starts_with(b"\x89PNG"));
+        assert_eq!(png, et.create_qr_png().unwrap());
+    }
+
+    #[test]
+    fn test_qr_png_scaled() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name).unwrap();
+
+        let small = image::load_from_memory(&et.create_qr_png_scaled(2).unwrap()).unwrap();
+        let large = image::load_from_memory(&et.create_qr_png_scaled(10).unwrap()).unwrap();
+        assert!(large.width() > small.width());
+        assert_eq!(large.width(), small.width() * 5);
+        assert_eq!(large.width(), large.height());
+
+        let mut prepared = rqrr::PreparedImage::prepare(large.to_luma8());
+        let grids = prepared.detect_grids();
+        assert_eq!(grids.len(), 1);
+        let (_, content) = grids[0].decode().unwrap();
+        assert_eq!(content, et.to_otpauth_uri().unwrap());
+
+        assert!(et.create_qr_png_scaled(0).is_err());
+        assert!(et.create_qr_png_scaled(u32::MAX).is_err());
+    }
+
+    #[test]
+    fn test_qr_mono_image() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name)
+            .unwrap()
+            .with_quiet_zone(2);
+        let matrix = et.qr_matrix().unwrap();
+
+        for module_px in [1, 3, 8] {
+            let image = et.qr_mono_image(module_px).unwrap();
+            let size = (u32::try_from(matrix.len()).unwrap() + 2 * 2) * module_px;
+            assert_eq!(image.dimensions(), (size, size));
+
+            // Every pixel is pure black or white, and matches its module exactly
+            for (x, y, pixel) in image.enumerate_pixels() {
+                let dark = padded_module(
+                    &matrix,
+                    2,
+                    (x / module_px) as usize,
+                    (y / module_px) as usize,
+                );
+                assert_eq!(pixel.0, [if dark { 0 } else { 255 }]);
+            }
+        }
+
+        // The same pixels as the PNG, and scannable
+        let png = image::load_from_memory(&et.create_qr_png_scaled(4).unwrap())
+            .unwrap()
+            .to_luma8();
+        let image = et.qr_mono_image(4).unwrap();
+        assert_eq!(image, png);
+        let mut prepared = rqrr::PreparedImage::prepare(image);
+        let (_, content) = prepared.detect_grids()[0].decode().unwrap();
+        assert_eq!(content, et.to_otpauth_uri().unwrap());
+
+        assert!(et.qr_mono_image(0).is_err());
+        assert!(et.qr_mono_image(u32::MAX).is_err());
+    }
+
+    #[test]
+    fn test_qr_pngs_multi() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name)
+            .unwrap()
+            .with_quiet_zone(2);
+
+        let sizes = [10, 2, 10, 5];
+        let pngs = et.create_qr_pngs_multi(&sizes).unwrap();
+        assert_eq!(pngs.len(), sizes.len());
+        // Identical to rendering each size separately, in the order requested
+        for (png, module_px) in pngs.iter().zip(sizes) {
+            assert_eq!(png, &et.create_qr_png_scaled(module_px).unwrap());
+        }
+
+        let mut prepared =
+            rqrr::PreparedImage::prepare(image::load_from_memory(&pngs[1]).unwrap().to_luma8());
+        let (_, content) = prepared.detect_grids()[0].decode().unwrap();
+        assert_eq!(content, et.to_otpauth_uri().unwrap());
+
+        assert!(et.create_qr_pngs_multi(&[]).unwrap().is_empty());
+        assert!(et.create_qr_pngs_multi(&[4, 0]).is_err());
+        assert!(et.create_qr_pngs_multi(&[u32::MAX, 4]).is_err());
+    }
+
+    #[test]
+    fn test_seconds_remaining() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+
+        for period in [1, 30, 90] {
+            let et = EasyTotp::new(issuer.clone(), account_name.clone())
+                .unwrap()
+                .with_period(period)
+                .unwrap();
+            assert!((1..=period).contains(&et.seconds_remaining().unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_verify_token_at() {
+        let raw_secret = Zeroizing::new(b"SUPERSecretSecretSecret".to_vec());
+        let et = EasyTotp {
+            raw_secret,
+            account_name: String::from("test@test-email.com"),
+            ..Default::default()
+        };
+
+        let now = 1_700_000_010;
+        let token = et.generate_token_at(now).unwrap();
+
+        assert!(et.verify_token_at(&token, now).unwrap());
+        assert!(et.verify_token_at(&token, now - 30).unwrap());
+        assert!(et.verify_token_at(&token, now + 30).unwrap());
+        assert!(!et.verify_token_at(&token, now + 60).unwrap());
+        assert!(!et.verify_token_at(&token, now - 60).unwrap());
+        assert!(!et.verify_token_at("garbage", now).unwrap());
+
+        // Windows reaching back before the Unix epoch must not underflow
+        let early_token = et.generate_token_at(0).unwrap();
+        assert!(et.verify_token_at(&early_token, 0).unwrap());
+        assert!(et.verify_token_at(&early_token, 30).unwrap());
+        assert!(!et.verify_token_at(&early_token, 60).unwrap());
+    }
+
+    #[test]
+    fn test_error_variants() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer.clone(), account_name.clone()).unwrap();
+
+        assert!(matches!(
+            et.clone().with_digits(9),
+            Err(EasyTotpError::InvalidSetting(_))
+        ));
+        assert!(matches!(
+            EasyTotp::from_base32_secret("not-base32", issuer.clone(), account_name.clone()),
+            Err(EasyTotpError::SecretParse(_))
+        ));
+        assert!(matches!(
+            EasyTotp::from_otpauth_uri("otpauth://hotp/x?secret=GEZDGNBV&counter=0"),
+            Err(EasyTotpError::InvalidUri(_))
+        ));
+
+        // A 5-byte secret is too short
+        let short = EasyTotp::from_base32_secret("GEZDGNBV", issuer, account_name).unwrap();
+        assert!(matches!(
+            short.to_otpauth_uri(),
+            Err(EasyTotpError::InvalidSetting(_))
+        ));
+
+        // `totp_rs` errors surface as the error source
+        let err = EasyTotp::from_otpauth_uri("otpauth://totp/McCormick:test?secret=GEZDGNBV")
+            .unwrap_err();
+        assert!(matches!(err, EasyTotpError::TotpConstruction(_)));
+        assert!(err.source().is_some());
+        assert!(
+            err.to_string()
+                .starts_with("EasyTotp encountered an error: ")
+        );
+    }
+
+    #[test]
+    fn test_debug_redacts_secret() {
+        let raw_secret = Zeroizing::new(b"SUPERSecretSecretSecret".to_vec());
+        let et = EasyTotp {
+            raw_secret: raw_secret.clone(),
+            issuer: Some(String::from("McCormick")),
+            account_name: String::from("test@test-email.com"),
+            ..Default::default()
+        };
+        // Populate the cached `TOTP`, which holds its own copy of the secret
+        et.generate_token().unwrap();
+
+        let debug = format!("{et:?}");
+        assert!(debug.contains("<redacted 23 bytes>"));
+        assert!(debug.contains("McCormick"));
+        assert!(debug.contains("test@test-email.com"));
+        assert!(!debug.contains("SUPERSecret"));
+        assert!(!debug.contains(&format!("{:?}", raw_secret.as_slice())));
+        assert!(!debug.contains(&et.secret_base32()));
+
+        // Each setting is printed once, under its own name
+        assert!(debug.contains("qr_ecc: Medium,"));
+        assert_eq!(debug.matches("quiet_zone").count(), 1);
+    }
+
+    #[test]
+    fn test_qr_shading_binary() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name)
+            .unwrap()
+            .with_qr_instructions(QRInstructions::Hidden);
+        let binary = et.clone().with_qr_shading(QRShading::Binary);
+        assert_eq!(binary, et);
+
+        for mode in [QRColorMode::Direct, QRColorMode::Inverted] {
+            // Only two glyphs in full size, whatever the width
+            for width in [40, 80, 200] {
+                let lines = binary.qr_text_sized(QRSize::Full, mode, width).unwrap();
+                let glyphs: HashSet<char> = lines.iter().flat_map(|line| line.chars()).collect();
+                assert_eq!(glyphs, HashSet::from(['█', ' ']));
+            }
+
+            // Mini size pairs them up into half blocks
+            let lines = binary.qr_text_sized(QRSize::Mini, mode, 80).unwrap();
+            assert!(
+                lines
+                    .iter()
+                    .flat_map(|line| line.chars())
+                    .all(|c| matches!(c, '█' | '▀' | '▄' | ' '))
+            );
+
+            // The built-in shadings are shorthands for their thresholds
+            for shading in [QRShading::Grayscale, QRShading::Binary] {
+                let custom = et
+                    .clone()
+                    .with_qr_shading(QRShading::Custom(shading.thresholds()));
+                assert_eq!(
+                    custom.qr_text_sized(QRSize::Full, mode, 40).unwrap(),
+                    et.clone()
+                        .with_qr_shading(shading)
+                        .qr_text_sized(QRSize::Full, mode, 40)
+                        .unwrap()
+                );
+            }
+
+            // Same layout as the grayscale rendering
+            let grayscale = et.qr_text_sized(QRSize::Full, mode, 80).unwrap();
+            let lines = binary.qr_text_sized(QRSize::Full, mode, 80).unwrap();
+            assert_eq!(lines.len(), grayscale.len());
+            assert_eq!(lines[0].chars().count(), grayscale[0].chars().count());
+        }
+    }
+
+    #[test]
+    fn test_qr_instructions() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name).unwrap();
+        let hidden = et.clone().with_qr_instructions(QRInstructions::Hidden);
+        let custom_lines = vec![String::from("Escanee el código QR."), String::new()];
+        let custom = et
+            .clone()
+            .with_qr_instructions(QRInstructions::Custom(custom_lines.clone()));
+        assert_eq!(hidden, et);
+
+        let english = qr_instructions("TOTP");
+        let outputs: [fn(&EasyTotp) -> Vec<String>; 4] = [
+            |et| {
+                et.qr_text_sized(QRSize::Mini, QRColorMode::Direct, 80)
+                    .unwrap()
+            },
+            |et| {
+                et.qr_text_with_width(QRSize::Full, QRColorMode::Direct, QRAmbiguousWidth::Wide)
+                    .unwrap()
+            },
+            |et| et.qr_text_ascii(QRColorMode::Inverted).unwrap(),
+            |et| et.qr_text_ansi(QRColorMode::Direct, true).unwrap(),
+        ];
+        for output in outputs {
+            let qr_only = output(&hidden);
+            assert!(!qr_only.is_empty());
+            assert!(
+                qr_only
+                    .iter()
+                    .all(|line| !line.chars().any(char::is_alphabetic) || line.contains('\x1b'))
+            );
+
+            let mut expected = qr_only.clone();
+            expected.extend(english.clone());
+            assert_eq!(output(&et), expected);
+
+            let mut expected = qr_only;
+            expected.extend(custom_lines.clone());
+            assert_eq!(output(&custom), expected);
+        }
+
+        // The QR code is the same size with or without instructions
+        assert_eq!(
+            hidden
+                .qr_text(QRSize::Full, QRColorMode::Direct)
+                .unwrap()
+                .len()
+                + english.len(),
+            et.qr_text(QRSize::Full, QRColorMode::Direct).unwrap().len()
+        );
+        hidden
+            .write_qr_terminal(&mut std::io::sink(), QRColorMode::Direct, QRSize::Mini)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_self_check() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name).unwrap();
+        et.self_check().unwrap();
+
+        for et in [
+            et.clone().with_algorithm(Algorithm::SHA1),
+            et.clone().with_algorithm(Algorithm::Steam),
+            et.clone().with_digits(8).unwrap().with_period(60).unwrap(),
+            et.clone().with_skew(0),
+            et.clone().with_clock(|| 0),
+            et.clone().with_clock(|| u64::MAX),
+        ] {
+            et.self_check().unwrap();
+        }
+
+        // Misconfigurations that slipped past the setters are caught. `..Default::default()` rather than `..et`,
+        // which would copy the cached `TOTP` instance as well.
+        let broken = [
+            EasyTotp {
+                raw_secret: et.raw_secret.clone(),
+                digits: 9,
+                ..Default::default()
+            },
+            EasyTotp {
+                raw_secret: et.raw_secret.clone(),
+                account_name: String::from("test:test-email.com"),
+                ..Default::default()
+            },
+            EasyTotp {
+                raw_secret: Zeroizing::new(vec![0; 4]),
+                ..Default::default()
+            },
+        ];
+        for et in broken {
+            assert!(et.self_check().is_err());
+        }
+
+        let message = EasyTotpError::SelfCheck(String::from("token rejected")).to_string();
+        assert!(message.contains("self-check failed: token rejected"));
+    }
+
+    #[test]
+    fn test_metadata() {
+        let et = rfc_account()
+            .with_algorithm(Algorithm::SHA256)
+            .with_digits(8)
+            .unwrap()
+            .with_period(60)
+            .unwrap();
+
+        let metadata = et.metadata();
+        assert_eq!(
+            metadata,
+            EasyTotpMetadata {
+                issuer: Some(String::from("McCormick")),
+                account_name: String::from("test@test-email.com"),
+                algorithm: Algorithm::SHA256,
+                digits: 8,
+                period: 60,
+            }
+        );
+        assert!(!format!("{metadata:?}").contains("GEZDGNBV"));
+
+        // The metadata outlives the credential
+        drop(et);
+        assert_eq!(metadata.issuer.as_deref(), Some("McCormick"));
+    }
+
+    #[test]
+    fn test_display() {
+        let raw_secret = Zeroizing::new(b"SUPERSecretSecretSecret".to_vec());
+        let et = EasyTotp {
+            raw_secret: raw_secret.clone(),
+            issuer: Some(String::from("McCormick")),
+            account_name: String::from("test@test-email.com"),
+            ..Default::default()
+        };
+
+        let display = et.to_string();
+        assert_eq!(
+            display,
+            "EasyTotp(issuer: McCormick, account: test@test-email.com, algorithm: SHA512, digits: 6, period: 30s)"
+        );
+        assert!(!display.contains(&et.secret_base32()));
+
+        let et = EasyTotp { issuer: None, ..et }.with_algorithm(Algorithm::Steam);
+        assert_eq!(
+            et.to_string(),
+            "EasyTotp(issuer: none, account: test@test-email.com, algorithm: Steam, digits: 5, period: 30s)"
+        );
+    }
+
+    #[test]
+    fn test_clones_own_their_secret() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name).unwrap();
+        let token = et.generate_token_at(1_700_000_000).unwrap();
+
+        // Dropping (and thereby zeroizing) the original must leave the clone's secret intact
+        let cloned = et.clone();
+        drop(et);
+        assert_eq!(cloned.generate_token_at(1_700_000_000).unwrap(), token);
+    }
+
+    #[test]
+    fn test_builder() {
+        let et = EasyTotp::builder()
+            .issuer("McCormick")
+            .account_name("test@test-email.com")
+            .algorithm(Algorithm::SHA1)
+            .digits(8)
+            .period(30)
+            .skew(0)
+            .secret_bytes(b"12345678901234567890")
+            .build()
+            .unwrap();
+        assert_eq!(et.generate_token_at(59).unwrap(), "94287082");
+        assert!(!et.verify_token_at("94287082", 60).unwrap());
+
+        // Defaults match `new`, with a freshly generated secret
+        let et = EasyTotp::builder()
+            .account_name("test@test-email.com")
+            .build()
+            .unwrap();
+        assert_eq!(et.raw_secret.len(), 20);
+        assert_eq!(et.algorithm, Algorithm::SHA512);
+        assert_eq!((et.digits, et.period, et.skew), (6, 30, 1));
+
+        let named = EasyTotp::builder().account_name("test@test-email.com");
+        let invalid = [
+            named.clone().digits(5),
+            named.clone().period(0),
+            named.clone().secret_bytes(b"too short"),
+            named.clone().issuer("Mc:Cormick"),
+            named.clone().require_issuer(),
+            named.clone().issuer(" ").require_issuer(),
+            named.clone().issuer(""),
+            EasyTotp::builder(),
+            EasyTotp::builder().account_name(" \t"),
+        ];
+        for builder in invalid {
+            assert!(builder.build().is_err());
+        }
+
+        let et = EasyTotp::builder()
+            .issuer("McCormick")
+            .account_name("test@test-email.com")
+            .require_issuer()
+            .build()
+            .unwrap();
+        assert_eq!(et.issuer.as_deref(), Some("McCormick"));
+    }
+
+    #[test]
+    fn test_qr_ecc_level() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name)
+            .unwrap()
+            .with_qr_ecc(QREccLevel::High);
+
+        // rqrr reports the raw format bits, in which High is 0b10
+        for png in [
+            et.create_qr_png().unwrap(),
+            et.create_qr_png_scaled(4).unwrap(),
+        ] {
+            let img = image::load_from_memory(&png).unwrap().to_luma8();
+            let mut img = rqrr::PreparedImage::prepare(img);
+            let grids = img.detect_grids();
+            assert_eq!(grids.len(), 1);
+            let (meta, content) = grids[0].decode().unwrap();
+            assert_eq!(meta.ecc_level, 2);
+            assert_eq!(content, et.to_otpauth_uri().unwrap());
+        }
+
+        // A higher level means a denser QR code, which shows up in every renderer
+        let medium = et.clone().with_qr_ecc(QREccLevel::Medium);
+        assert!(et.create_qr_svg().unwrap().len() > medium.create_qr_svg().unwrap().len());
+        assert!(
+            et.qr_text(QRSize::Full, QRColorMode::Direct).unwrap().len()
+                > medium
+                    .qr_text(QRSize::Full, QRColorMode::Direct)
+                    .unwrap()
+                    .len()
+        );
+    }
+
+    #[test]
+    fn test_qr_text_ascii() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name).unwrap();
+        let qr = encode_qr(&et.to_otpauth_uri().unwrap(), QREccLevel::Medium).unwrap();
+        let modules = usize::try_from(qr.size()).unwrap() + 8;
+
+        for mode in [QRColorMode::Direct, QRColorMode::Inverted] {
+            let lines = et.qr_text_ascii(mode).unwrap();
+            let (qr_lines, text_lines) = lines.split_at(modules);
+
+            for line in qr_lines {
+                assert_eq!(line.len(), modules * 2);
+                assert!(line.chars().all(|c| c == '#' || c == ' '));
+                assert!(!line.contains('\n'));
+            }
+            assert!(text_lines[0].starts_with("Scan the above QR code"));
+            assert!(text_lines.iter().all(|line| line.is_ascii()));
+        }
+
+        // Direct mode draws dark modules with `#`, so the quiet zone is blank; inverted mode is the reverse
+        let direct = et.qr_text_ascii(QRColorMode::Direct).unwrap();
+        let inverted = et.qr_text_ascii(QRColorMode::Inverted).unwrap();
+        assert!(direct[0].chars().all(|c| c == ' '));
+        assert!(inverted[0].chars().all(|c| c == '#'));
+    }
+
+    #[test]
+    fn test_qr_text_ansi() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name).unwrap();
+        let ascii = et.qr_text_ascii(QRColorMode::Direct).unwrap();
+
+        for (truecolor, ink, paper) in [
+            (true, "\x1b[48;2;0;0;0m  ", "\x1b[48;2;255;255;255m  "),
+            (false, "\x1b[40m  ", "\x1b[107m  "),
+        ] {
+            let lines = et.qr_text_ansi(QRColorMode::Direct, truecolor).unwrap();
+            assert_eq!(lines.len(), ascii.len());
+
+            // Module for module, the same picture as the ASCII rendering
+            for (line, ascii_line) in lines.iter().zip(&ascii) {
+                let Some(cells) = line.strip_suffix("\x1b[0m") else {
+                    assert_eq!(line, ascii_line);
+                    continue;
+                };
+                assert_eq!(cells.replace(ink, "##").replace(paper, "  "), *ascii_line);
+            }
+        }
+    }
+
+    #[test]
+    fn test_qr_terminal_inverted() {
+        assert_eq!(invert_block_line("████"), "    ");
+        assert_eq!(invert_block_line("    "), "████");
+        assert_eq!(invert_block_line("█ ▒▓"), " █▓░");
+
+        let raw_secret = Zeroizing::new(b"SUPERSecretSecretSecret".to_vec());
+        let et = EasyTotp {
+            raw_secret,
+            issuer: Some(String::from("McCormick")),
+            account_name: String::from("test@test-email.com"),
+            ..Default::default()
+        };
+
+        let instructions = qr_instructions("TOTP");
+        let direct = et
+            .qr_text_sized(QRSize::Full, QRColorMode::Direct, 100)
+            .unwrap();
+        let inverted = et
+            .qr_text_sized(QRSize::Full, QRColorMode::Inverted, 100)
+            .unwrap();
+        let rows = direct.len() - instructions.len();
+
+        // Every QR row is inverted, including the all-light quiet zone, and the instructions are left alone
+        assert!(direct[0].chars().all(|c| c == ' '));
+        assert!(inverted[0].chars().all(|c| c == '█'));
+        for (direct_row, inverted_row) in direct[..rows].iter().zip(&inverted[..rows]) {
+            assert_eq!(&invert_block_line(direct_row), inverted_row);
+        }
+        assert!(inverted.ends_with(&instructions));
+    }
+
+    #[test]
+    fn test_new_with_secret_length() {
+        for bytes in [16, 20, 32, 64] {
+            let issuer = Some(String::from("McCormick"));
+            let account_name = String::from("test@test-email.com");
+            let et = EasyTotp::new_with_secret_length(issuer, account_name, bytes).unwrap();
+            assert_eq!(et.raw_secret.len(), bytes);
+
+            let token = et.generate_token_at(1_700_000_000).unwrap();
+            assert!(et.verify_token_at(&token, 1_700_000_000).unwrap());
+        }
+
+        for bytes in [0, 15] {
+            assert!(matches!(
+                EasyTotp::new_with_secret_length(None, String::from("test@test-email.com"), bytes),
+                Err(EasyTotpError::InvalidSetting(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_qr_image_formats() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name).unwrap();
+        let uri = et.to_otpauth_uri().unwrap();
+
+        for format in [ImageFormat::Png, ImageFormat::Jpeg, ImageFormat::WebP] {
+            let data = et.create_qr_image(format).unwrap();
+            assert_eq!(image::guess_format(&data).unwrap(), format);
+
+            let img = image::load_from_memory(&data).unwrap().to_luma8();
+            let mut img = rqrr::PreparedImage::prepare(img);
+            let grids = img.detect_grids();
+            assert_eq!(grids.len(), 1);
+            assert_eq!(grids[0].decode().unwrap().1, uri);
+        }
+
+        assert_eq!(
+            et.create_qr_png().unwrap(),
+            et.create_qr_image(ImageFormat::Png).unwrap()
+        );
+
+        // DDS can only be decoded by the `image` crate
+        let error = et.create_qr_image(ImageFormat::Dds).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<EasyTotpError>(),
+            Some(EasyTotpError::InvalidSetting(_))
+        ));
+    }
+
+    #[test]
+    fn test_quiet_zone() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let et = EasyTotp::new(issuer, account_name).unwrap();
+        let qr = encode_qr(&et.to_otpauth_uri().unwrap(), QREccLevel::Medium).unwrap();
+        let modules = qr.size().unsigned_abs();
+
+        for quiet_zone in [0, 4, 10] {
+            let padded = et.clone().with_quiet_zone(quiet_zone);
+            let border = u32::from(quiet_zone);
+
+            let png = image::load_from_memory(&padded.create_qr_png().unwrap()).unwrap();
+            assert_eq!(png.width(), (modules + border * 2) * 8);
+            assert_eq!(png.height(), (modules + border * 2) * 8);
+
+            let scaled = padded.create_qr_png_scaled(3).unwrap();
+            let scaled = image::load_from_memory(&scaled).unwrap();
+            assert_eq!(scaled.width(), (modules + border * 2) * 3);
+
+            let ascii = padded.qr_text_ascii(QRColorMode::Direct).unwrap();
+            assert_eq!(ascii[0].len(), (modules + border * 2) as usize * 2);
+
+            let svg = padded.create_qr_svg().unwrap();
+            let dimension = modules + border * 2;
+            assert!(svg.contains(&format!("viewBox=\"0 0 {dimension} {dimension}\"")));
+        }
+
+        // The border is light, so the corner pixel of a padded QR code is white
+        let png = et.clone().with_quiet_zone(10).create_qr_png().unwrap();
+        let png = image::load_from_memory(&png).unwrap().to_luma8();
+        assert_eq!(png.get_pixel(0, 0)[0], 255);
+    }
+
+    #[test]
+    fn test_label_validation() {
+        let secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+        // A `:` would make the `issuer:account_name` label ambiguous
+        for (issuer, account_name) in [
+            (Some("Mc:Cormick"), "test@test-email.com"),
+            (Some("McCormick"), "test:test-email.com"),
+            (None, "McCormick:test@test-email.com"),
+        ] {
+            let issuer = issuer.map(String::from);
+            let account_name = String::from(account_name);
+            assert!(matches!(
+                EasyTotp::from_base32_secret(secret, issuer.clone(), account_name.clone()),
+                Err(EasyTotpError::InvalidSetting(_))
+            ));
+            assert!(matches!(
+                EasyTotp::new_with_secret_length(issuer.clone(), account_name.clone(), 20),
+                Err(EasyTotpError::InvalidSetting(_))
+            ));
+            assert!(matches!(
+                EasyTotp::new(issuer, account_name),
+                Err(EasyTotpError::InvalidSetting(_))
+            ));
+        }
+
+        // Other special characters are percent-encoded, and survive a round trip
+        for (issuer, account_name, label) in [
+            ("Mc/Cormick", "test/user", "Mc%2FCormick:test%2Fuser"),
+            ("McCormick?", "test?user", "McCormick%3F:test%3Fuser"),
+            ("Mc Cormick", "test user", "Mc%20Cormick:test%20user"),
+        ] {
+            let et = EasyTotp::from_base32_secret(
+                secret,
+                Some(String::from(issuer)),
+                String::from(account_name),
+            )
+            .unwrap();
+            let uri = et.to_otpauth_uri().unwrap();
+            assert!(
+                uri.starts_with(&format!("otpauth://totp/{label}?")),
+                "{uri}"
+            );
+
+            let parsed = EasyTotp::from_otpauth_uri(&uri).unwrap();
+            assert_eq!(parsed.issuer.as_deref(), Some(issuer));
+            assert_eq!(parsed.account_name, account_name);
+        }
+    }
+
+    #[test]
+    fn test_totp_conversion() {
+        let et = rfc_account()
+            .with_algorithm(Algorithm::SHA256)
+            .with_digits(8)
+            .unwrap()
+            .with_period(60)
+            .unwrap()
+            .with_skew(2);
+
+        let totp = et.to_totp().unwrap();
+        assert_eq!(totp.algorithm, Algorithm::SHA256);
+        assert_eq!(totp.digits, 8);
+        assert_eq!(totp.step, 60);
+        assert_eq!(totp.skew, 2);
+        assert_eq!(totp.secret, b"12345678901234567890");
+        assert_eq!(totp.issuer.as_deref(), Some("McCormick"));
+        assert_eq!(totp.account_name, "test@test-email.com");
+
+        let converted = EasyTotp::try_from(totp.clone()).unwrap();
+        assert_eq!(converted, et);
+        assert_eq!(converted.generate_token_at(59).unwrap(), totp.generate(59));
+
+        // `TOTP::new_unchecked` skips the checks, so the conversion must do them
+        let short = TOTP::new_unchecked(
+            Algorithm::SHA1,
+            6,
+            1,
+            30,
+            b"too short".to_vec(),
+            None,
+            String::from("test@test-email.com"),
+        );
+        assert!(matches!(
+            EasyTotp::try_from(short),
+            Err(EasyTotpError::InvalidSetting(_))
+        ));
+
+        let bad_digits = TOTP::new_unchecked(
+            Algorithm::SHA1,
+            4,
+            1,
+            30,
+            b"12345678901234567890".to_vec(),
+            None,
+            String::from("test@test-email.com"),
+        );
+        assert!(matches!(
+            EasyTotp::try_from(bad_digits),
+            Err(EasyTotpError::InvalidSetting(_))
+        ));
+    }
+
+    #[test]
+    fn test_current_and_next_token() {
+        let et = rfc_account();
+
+        let before = unix_time().unwrap();
+        let (current, next) = et.current_and_next_token().unwrap();
+        let after = unix_time().unwrap();
+
+        // Only comparable if the clock didn't cross a step boundary during the call
+        if before / et.period == after / et.period {
+            assert_eq!(current, et.generate_token_at(before).unwrap());
+            assert_eq!(next, et.generate_token_at(before + et.period).unwrap());
+        }
+        assert!(et.verify_token(&current).unwrap());
+    }
+
+    #[test]
+    fn test_token_info() {
+        let et = rfc_account();
+
+        let before = unix_time().unwrap();
+        let info = et.token_info().unwrap();
+        let after = unix_time().unwrap();
+
+        assert!((before / et.period..=after / et.period).contains(&info.step));
+        assert!((1..=et.period).contains(&info.seconds_remaining));
+        assert_eq!(
+            info.code,
+            et.generate_token_at(info.step * et.period).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_verify_token_once() {
+        let et = rfc_account()
+            .with_algorithm(Algorithm::SHA1)
+            .with_digits(8)
+            .unwrap();
+
+        // RFC 6238 SHA1 vector, in step 37_037_036
+        let mut last_used_step = None;
+        assert!(
+            et.verify_token_once_at("07081804", 1_111_111_109, &mut last_used_step)
+                .unwrap()
+        );
+        assert_eq!(last_used_step, Some(37_037_036));
+
+        // Replays are rejected, even from later in the skew window
+        assert!(
+            !et.verify_token_once_at("07081804", 1_111_111_109, &mut last_used_step)
+                .unwrap()
+        );
+        assert!(
+            !et.verify_token_once_at("07081804", 1_111_111_139, &mut last_used_step)
+                .unwrap()
+        );
+
+        // The next step's token is still accepted
+        let next = et.generate_token_at(1_111_111_139).unwrap();
+        assert!(
+            et.verify_token_once_at(&next, 1_111_111_139, &mut last_used_step)
+                .unwrap()
+        );
+        assert_eq!(last_used_step, Some(37_037_037));
+
+        // Wrong or malformed tokens never update the step
+        let mut last_used_step = None;
+        for token in ["00000000", "0708180", "not a token"] {
+            assert!(
+                !et.verify_token_once_at(token, 1_111_111_109, &mut last_used_step)
+                    .unwrap()
+            );
+        }
+        assert_eq!(last_used_step, None);
+    }
+
+    #[test]
+    fn test_default_consts() {
+        let et = EasyTotp::new(None, String::from("test@test-email.com")).unwrap();
+        assert_eq!(et.algorithm, EasyTotp::DEFAULT_ALGORITHM);
+        assert_eq!(et.digits, EasyTotp::DEFAULT_DIGITS);
+        assert_eq!(et.period, EasyTotp::DEFAULT_PERIOD);
+        assert_eq!(et.skew, EasyTotp::DEFAULT_SKEW);
+
+        let built = EasyTotp::builder()
+            .account_name("test@test-email.com")
+            .build()
+            .unwrap();
+        assert_eq!(built.algorithm, EasyTotp::DEFAULT_ALGORITHM);
+        assert_eq!(built.digits, EasyTotp::DEFAULT_DIGITS);
+        assert_eq!(built.period, EasyTotp::DEFAULT_PERIOD);
+        assert_eq!(built.skew, EasyTotp::DEFAULT_SKEW);
+    }
+
+    #[test]
+    fn test_steam_guard() {
+        // Vectors from the ValvePython `steam` library, for the secret "superdupersecret"
+        let et = EasyTotp::from_steam_shared_secret(
+            "c3VwZXJkdXBlcnNlY3JldA==",
+            String::from("test@test-email.com"),
+        )
+        .unwrap();
+        assert_eq!(et.algorithm, Algorithm::Steam);
+        assert_eq!(et.issuer.as_deref(), Some("Steam"));
+        assert_eq!(et.generate_token_at(3_000_029).unwrap(), "94R9D");
+        assert_eq!(et.generate_token_at(3_000_030).unwrap(), "YRGQJ");
+
+        assert!(et.verify_token_at("YRGQJ", 3_000_030).unwrap());
+        assert!(et.verify_token_at("94R9D", 3_000_030).unwrap());
+        assert!(!et.verify_token_at("AAAAA", 3_000_030).unwrap());
+        assert!(!et.verify_token_at("123456", 3_000_030).unwrap());
+
+        // The same secret in base32 gives the same tokens
+        let from_base32 = EasyTotp::from_base32_secret(
+            "ON2XAZLSMR2XAZLSONSWG4TFOQ",
+            Some(String::from("Steam")),
+            String::from("test@test-email.com"),
+        )
+        .unwrap()
+        .with_algorithm(Algorithm::Steam);
+        assert_eq!(from_base32, et);
+
+        // The format is fixed for Steam, and restored when switching away
+        assert!(matches!(
+            et.clone().with_digits(6),
+            Err(EasyTotpError::InvalidSetting(_))
+        ));
+        assert!(matches!(
+            et.clone().with_period(60),
+            Err(EasyTotpError::InvalidSetting(_))
+        ));
+        let sha1 = et.clone().with_algorithm(Algorithm::SHA1);
+        assert_eq!((sha1.digits, sha1.period), (6, 30));
+        assert_eq!(sha1.generate_token_at(3_000_030).unwrap().len(), 6);
+
+        // Steam otpauth URIs round-trip
+        let uri = et.to_otpauth_uri().unwrap();
+        assert!(uri.starts_with("otpauth://steam/Steam:test%40test-email.com?"));
+        assert_eq!(EasyTotp::from_otpauth_uri(&uri).unwrap(), et);
+
+        assert!(matches!(
+            EasyTotp::from_steam_shared_secret("not base64!", String::from("test")),
+            Err(EasyTotpError::SecretParse(_))
+        ));
+        assert!(matches!(
+            EasyTotp::from_steam_shared_secret("dG9vIHNob3J0", String::from("test")),
+            Err(EasyTotpError::InvalidSetting(_))
+        ));
+    }
+
+    #[test]
+    fn test_clock_injection() {
+        let et = rfc_account()
+            .with_algorithm(Algorithm::SHA1)
+            .with_digits(8)
+            .unwrap()
+            .with_clock(|| 1_111_111_109);
+
+        // RFC 6238 SHA1 vector
+        assert_eq!(et.generate_token().unwrap(), "07081804");
+        assert!(et.verify_token("07081804").unwrap());
+        assert_eq!(et.seconds_remaining().unwrap(), 1);
+
+        let info = et.token_info().unwrap();
+        assert_eq!(info.code, "07081804");
+        assert_eq!(info.step, 37_037_036);
+        assert_eq!(info.seconds_remaining, 1);
+
+        let (current, next) = et.current_and_next_token().unwrap();
+        assert_eq!(current, "07081804");
+        assert_eq!(next, et.generate_token_at(1_111_111_139).unwrap());
+
+        let mut last_used_step = None;
+        assert!(
+            et.verify_token_once("07081804", &mut last_used_step)
+                .unwrap()
+        );
+        assert_eq!(last_used_step, Some(37_037_036));
+
+        // Clones share the clock, and the clock is not a setting
+        let clone = et.clone();
+        assert_eq!(clone.generate_token().unwrap(), "07081804");
+        assert_eq!(clone.with_clock(|| 0), et);
+    }
+
+    #[test]
+    fn test_qr_image_buffer() {
+        let et = rfc_account();
+
+        let image = et.create_qr_image_buffer().unwrap();
+        assert_eq!(image.width(), image.height());
+        assert_eq!(image.width() % 8, 0);
+
+        // The PNG is exactly this image, encoded
+        let png = image::load_from_memory(&et.create_qr_png().unwrap()).unwrap();
+        assert_eq!(png.to_luma8(), image.to_luma8());
+    }
+
+    #[test]
+    fn test_min_secret_length() {
+        // The 10-byte secret from many legacy examples
+        let legacy = EasyTotp::from_base32_secret(
+            "JBSWY3DPEHPK3PXP",
+            Some(String::from("McCormick")),
+            String::from("test@test-email.com"),
+        )
+        .unwrap();
+
+        for result in [
+            legacy.generate_token_at(0).map_err(|e| e.to_string()),
+            legacy.to_otpauth_uri().map_err(|e| e.to_string()),
+        ] {
+            assert!(result.unwrap_err().contains("at least 16 bytes, not 10"));
+        }
+
+        let legacy = legacy.with_min_secret_length(10);
+        let token = legacy.generate_token_at(0).unwrap();
+        assert_eq!(token.len(), 6);
+        assert!(legacy.verify_token_at(&token, 0).unwrap());
+
+        // The threshold can be raised too
+        let strict = EasyTotp::new(None, String::from("test@test-email.com"))
+            .unwrap()
+            .with_min_secret_length(32);
+        assert!(
+            strict
+                .generate_token_at(0)
+                .unwrap_err()
+                .to_string()
+                .contains("at least 32 bytes, not 20")
+        );
+
+        let builder = EasyTotp::builder()
+            .issuer("McCormick")
+            .account_name("test@test-email.com")
+            .secret_bytes(&legacy.raw_secret);
+        assert!(builder.clone().build().is_err());
+        assert_eq!(builder.min_secret_length(10).build().unwrap(), legacy);
+    }
+
+    #[test]
+    fn test_same_credential() {
+        let secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+        let et = EasyTotp::from_base32_secret(
+            secret,
+            Some(String::from("McCormick")),
+            String::from("test@test-email.com"),
+        )
+        .unwrap();
+
+        let relabeled = EasyTotp::from_base32_secret(secret, None, String::from("other"))
+            .unwrap()
+            .with_skew(3)
+            .with_qr_ecc(QREccLevel::High);
+        assert!(et.same_credential(&relabeled));
+        assert!(relabeled.same_credential(&et));
+
+        let other_secret = EasyTotp::from_base32_secret(
+            "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJR",
+            Some(String::from("McCormick")),
+            String::from("test@test-email.com"),
+        )
+        .unwrap();
+        for different in [
+            other_secret,
+            et.clone().with_algorithm(Algorithm::SHA1),
+            et.clone().with_digits(8).unwrap(),
+            et.clone().with_period(60).unwrap(),
+        ] {
+            assert!(!et.same_credential(&different));
+        }
+    }
+
+    #[test]
+    fn test_qr_text_ambiguous_width() {
+        let et = rfc_account();
+
+        // Block characters are single-width in most terminals
+        for size in [QRSize::Full, QRSize::Mini] {
+            assert_eq!(
+                et.qr_text_with_width(size, QRColorMode::Inverted, QRAmbiguousWidth::Narrow)
+                    .unwrap(),
+                et.qr_text(size, QRColorMode::Inverted).unwrap()
+            );
+        }
+
+        // ...but double-width in CJK terminals, so ASCII is used instead
+        for size in [QRSize::Full, QRSize::Mini] {
+            assert_eq!(
+                et.qr_text_with_width(size, QRColorMode::Inverted, QRAmbiguousWidth::Wide)
+                    .unwrap(),
+                et.qr_text_ascii(QRColorMode::Inverted).unwrap()
+            );
         }
+
+        assert_eq!(QRAmbiguousWidth::Wide.char_width('█'), Some(2));
+        assert_eq!(QRAmbiguousWidth::Wide.char_width('#'), Some(1));
     }
 
-    /// Render the QR code in the terminal
-    ///
-    /// BEWARE: terminal will display secret!!
-    ///
-    /// This function has been tested and has thus far received mixed results depending on the authenticator app used (Aegis seems to work well, whereas Proton Authenticator has trouble scanning from terminal). Your mileage may vary.
-    fn render_qr_terminal_full_direct(&self) -> Result<(), Box<dyn Error>> {
-        for line in self.qr_text(TerminalQRSize::Full, QRColorMode::Direct)? {
-            println!("{line}");
+    #[test]
+    fn test_qr_matrix() {
+        let et = rfc_account();
+
+        let matrix = et.qr_matrix().unwrap();
+        let qr = encode_qr(&et.to_otpauth_uri().unwrap(), QREccLevel::Medium).unwrap();
+        assert_eq!(matrix.len(), qr.size().unsigned_abs() as usize);
+        for (y, row) in (0..qr.size()).zip(&matrix) {
+            assert_eq!(row.len(), matrix.len());
+            for (x, &dark) in (0..qr.size()).zip(row) {
+                assert_eq!(dark, qr.get_module(x, y));
+            }
         }
-        Ok(())
-    }
 
-    #[allow(dead_code)]
-    /// Render the mini QR code in the terminal
-    ///
-    /// BEWARE: terminal will display secret!!
-    fn render_qr_terminal_mini_direct(&self) -> Result<(), Box<dyn Error>> {
-        for line in self.qr_text(TerminalQRSize::Mini, QRColorMode::Direct)? {
-            println!("{line}");
+        // The top-left finder pattern starts with a dark row of 7 modules
+        assert!(matrix[0][..7].iter().all(|&dark| dark));
+        assert!(!matrix[0][7]);
+
+        // The PNG is drawn from the same matrix, after the quiet zone
+        let image = et.create_qr_image_buffer().unwrap().to_luma8();
+        for (y, row) in (4..).zip(&matrix) {
+            for (x, &dark) in (4..).zip(row) {
+                let pixel = image.get_pixel(x * 8, y * 8)[0];
+                assert_eq!(pixel == 0, dark);
+            }
         }
-        Ok(())
     }
 
-    /// Render the QR code in the terminal, inverted colors
-    ///
-    /// BEWARE: terminal will display secret!!
-    fn render_qr_terminal_full_inverted(&self) -> Result<(), Box<dyn Error>> {
-        for line in self.qr_text(TerminalQRSize::Full, QRColorMode::Inverted)? {
-            println!("{line}");
+    #[test]
+    fn test_diagnose_token() {
+        let et = rfc_account()
+            .with_algorithm(Algorithm::SHA1)
+            .with_digits(8)
+            .unwrap();
+        let now: u64 = 1_111_111_109;
+
+        for offset in -10_i64..=10 {
+            let device_time = now.checked_add_signed(offset * 30).unwrap();
+            let token = et.generate_token_at(device_time).unwrap();
+            assert_eq!(et.diagnose_token_at(&token, now).unwrap(), Some(offset));
         }
-        Ok(())
+        for offset in [-11_i64, 11, 100] {
+            let device_time = now.checked_add_signed(offset * 30).unwrap();
+            let token = et.generate_token_at(device_time).unwrap();
+            assert_eq!(et.diagnose_token_at(&token, now).unwrap(), None);
+        }
+
+        // RFC 6238 test vector, from a device 5 steps ahead
+        assert_eq!(
+            et.diagnose_token_at("07081804", now - 150).unwrap(),
+            Some(5)
+        );
+        assert_eq!(et.diagnose_token_at("not a token", now).unwrap(), None);
+        assert_eq!(et.diagnose_token_at("", now).unwrap(), None);
+
+        // The window stops at the Unix epoch
+        assert_eq!(et.diagnose_token_at("94287082", 0).unwrap(), Some(1));
+        let first = et.generate_token_at(0).unwrap();
+        assert_eq!(et.diagnose_token_at(&first, 59).unwrap(), Some(-1));
+
+        let et = et.with_clock(move || now + 60);
+        assert_eq!(et.diagnose_token("07081804").unwrap(), Some(-2));
     }
 
-    #[allow(dead_code)]
-    /// Render the mini QR code in the terminal, inverted colors
-    ///
-    /// BEWARE: terminal will display secret!!
-    fn render_qr_terminal_mini_inverted(&self) -> Result<(), Box<dyn Error>> {
-        for line in self.qr_text(TerminalQRSize::Mini, QRColorMode::Inverted)? {
-            println!("{line}");
+    #[test]
+    fn test_acceptable_tokens() {
+        let et = rfc_account().with_skew(2);
+
+        let tokens = et.acceptable_tokens_at(1_700_000_000).unwrap();
+        assert_eq!(tokens.len(), 5);
+        for (offset, token) in (-2_i64..=2).zip(&tokens) {
+            let unix_time = 1_700_000_000_u64.saturating_add_signed(offset * 30);
+            assert_eq!(token, &et.generate_token_at(unix_time).unwrap());
+            assert!(et.verify_token_at(token, 1_700_000_000).unwrap());
         }
-        Ok(())
+
+        // Clipped at the Unix epoch, like verification
+        let tokens = et.acceptable_tokens_at(30).unwrap();
+        assert_eq!(tokens.len(), 4);
+        assert_eq!(tokens[0], et.generate_token_at(0).unwrap());
+
+        let et = et.with_clock(|| 1_700_000_000);
+        assert_eq!(
+            et.acceptable_tokens().unwrap(),
+            et.acceptable_tokens_at(1_700_000_000).unwrap()
+        );
     }
 
-    /// Generates a TOTP token for authentication
-    ///
-    /// ## Errors
-    /// This function will return an error if the TOTP generation fails.
-    pub fn generate_token(&self) -> Result<String, Box<dyn Error>> {
-        Ok(self.new_totp()?.generate_current()?)
+    #[test]
+    fn test_enroll() {
+        let et = rfc_account();
+
+        let info = et.enroll(QRColorMode::Inverted).unwrap();
+        assert_eq!(info.uri, et.to_otpauth_uri().unwrap());
+        assert_eq!(info.secret_base32, "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+        assert_eq!(
+            info.qr_lines,
+            et.qr_text(QRSize::Full, QRColorMode::Inverted).unwrap()
+        );
+
+        let printed = info.to_string();
+        assert!(printed.starts_with(&info.qr_lines[0]));
+        assert!(printed.contains("Secret key: GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"));
+        assert!(printed.ends_with(&info.uri));
+
+        let debug = format!("{info:?}");
+        assert!(!debug.contains("GEZDGNBVGY3TQOJQ"));
+        assert!(!debug.contains("otpauth://"));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use image;
-    use rqrr;
-    use std::fs;
-    use std::io::Write;
-    use std::{thread, time};
+    #[test]
+    fn test_qr_png_with_logo() {
+        let et = rfc_account();
+
+        // A solid logo, wider than it is tall, hides every module behind it
+        let logo =
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(200, 100, Rgba([200, 0, 0, 255])));
+        let png = et.create_qr_png_with_logo(&logo, 0.25).unwrap();
+        let image = image::load_from_memory(&png).unwrap().to_rgba8();
+
+        // The center is covered by the logo
+        let (width, height) = image.dimensions();
+        assert_eq!(
+            image.get_pixel(width / 2, height / 2),
+            &Rgba([200, 0, 0, 255])
+        );
+
+        // Still scannable, thanks to High error correction
+        let mut decoder = rqrr::PreparedImage::prepare(DynamicImage::ImageRgba8(image).to_luma8());
+        let grids = decoder.detect_grids();
+        assert_eq!(grids.len(), 1);
+        let (meta, content) = grids[0].decode().unwrap();
+        assert_eq!(meta.ecc_level, 2);
+        assert_eq!(content, et.to_otpauth_uri().unwrap());
+
+        for size_fraction in [0.0, -0.1, 0.26, 1.0, f32::NAN, f32::INFINITY] {
+            assert!(
+                et.create_qr_png_with_logo(&logo, size_fraction).is_err(),
+                "{size_fraction} should be rejected"
+            );
+        }
+    }
 
     #[test]
-    fn test_qr_png() {
+    fn test_qr_png_colored() {
+        let et = rfc_account();
+
+        let (navy, cream) = ([0, 0, 128], [255, 253, 208]);
+        let png = et.create_qr_png_colored(navy, cream).unwrap();
+        let image = image::load_from_memory(&png).unwrap().to_rgb8();
+
+        // Same layout as the plain PNG, in the given colors
+        let plain = et.create_qr_image_buffer().unwrap().to_luma8();
+        assert_eq!(image.dimensions(), plain.dimensions());
+        for (colored, plain) in image.pixels().zip(plain.pixels()) {
+            assert_eq!(colored, &Rgb(if plain[0] == 0 { navy } else { cream }));
+        }
+
+        let mut decoder = rqrr::PreparedImage::prepare(DynamicImage::ImageRgb8(image).to_luma8());
+        let (_, content) = decoder.detect_grids()[0].decode().unwrap();
+        assert_eq!(content, et.to_otpauth_uri().unwrap());
+
+        // Low contrast, and light modules on a dark background
+        for (dark, light) in [
+            ([128, 128, 128], [150, 150, 150]),
+            ([255, 255, 255], [0, 0, 0]),
+            ([0, 0, 0], [0, 0, 0]),
+        ] {
+            assert!(matches!(
+                et.create_qr_png_colored(dark, light)
+                    .unwrap_err()
+                    .downcast_ref::<EasyTotpError>(),
+                Some(EasyTotpError::InvalidSetting(_))
+            ));
+        }
+
+        assert!((relative_luminance([0, 0, 0])).abs() < 1e-9);
+        assert!((relative_luminance([255, 255, 255]) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_secret_entropy_bits() {
         let issuer = Some(String::from("McCormick"));
         let account_name = String::from("test@test-email.com");
-        let filename = "./test_images/qr_code.png";
 
-        let et = EasyTotp::new(issuer, account_name).unwrap();
+        let et = EasyTotp::new(issuer.clone(), account_name.clone()).unwrap();
+        assert_eq!(et.secret_entropy_bits(), 160);
 
-        let my_qr_code = et.create_qr_png();
+        let et =
+            EasyTotp::new_with_secret_length(issuer.clone(), account_name.clone(), 64).unwrap();
+        assert_eq!(et.secret_entropy_bits(), 512);
 
-        match my_qr_code {
-            Ok(png_data) => {
-                let mut file = fs::File::create(filename).unwrap();
-                file.write_all(&png_data).unwrap();
-                println!("QR code saved as 'qr_code.png'");
-            }
-            Err(e) => {
-                panic!("Error creating QR code: {:?}", e);
-            }
-        }
+        let et = EasyTotp::from_base32_secret("GEZDGNBVGY3TQOJQ", issuer, account_name).unwrap();
+        assert_eq!(et.secret_entropy_bits(), 80);
+    }
 
-        let img = image::open(filename).unwrap().to_luma8();
-        // Prepare for detection
-        let mut img = rqrr::PreparedImage::prepare(img);
-        // Search for grids, without decoding
-        let grids = img.detect_grids();
-        assert_eq!(grids.len(), 1);
-        // Decode the grid
-        let (meta, content) = grids[0].decode().unwrap();
-        assert_eq!(meta.ecc_level, 0);
-        assert!(content.starts_with("otpauth://totp/McCormick:test%40test-email.com?secret="));
-        assert!(content.contains("&algorithm=SHA512&issuer=McCormick"));
+    #[test]
+    fn test_to_ascii_otpauth_uri() {
+        let secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+        let et = EasyTotp::from_base32_secret(
+            secret,
+            Some(String::from("株式会社 McCormick")),
+            String::from("José.Ñúñez+2fa@test-email.com"),
+        )
+        .unwrap()
+        .with_digits(8)
+        .unwrap();
 
-        // Extract and validate the secret parameter
-        let secret_start = content.find("secret=").unwrap() + 7;
-        let secret_end = content.find("&algorithm=").unwrap();
-        let secret = &content[secret_start..secret_end];
+        let (uri, label) = et
+            .to_ascii_otpauth_uri(EasyTotp::ASCII_LABEL_PUNCTUATION)
+            .unwrap();
+        assert_eq!(label.issuer.as_deref(), Some("McCormick"));
+        assert_eq!(label.account_name, "Jos.ez+2fa@test-email.com");
+        assert_eq!(
+            label.dropped,
+            vec!['株', '式', '会', '社', 'é', 'Ñ', 'ú', 'ñ']
+        );
+        assert!(uri.is_ascii());
 
-        // Ensure the secret is not empty and has reasonable length
-        assert!(!secret.is_empty());
+        // Only the label changes
+        let imported = EasyTotp::from_otpauth_uri(&uri).unwrap();
+        assert_eq!(imported.issuer, label.issuer);
+        assert_eq!(imported.account_name, label.account_name);
+        assert!(imported.same_credential(&et));
+        assert_eq!(et.issuer.as_deref(), Some("株式会社 McCormick"));
         assert!(
-            secret.len() >= 20,
-            "Secret should be at least 20 characters long"
+            EasyTotp::from_otpauth_uri(&et.to_otpauth_uri().unwrap())
+                .unwrap()
+                .account_name
+                .starts_with("José")
         );
 
-        // Verify the secret only contains valid base32 characters (A-Z, 2-7)
-        assert!(
-            secret
-                .chars()
-                .all(|c| c.is_ascii_uppercase() || "234567".contains(c)),
-            "Secret should only contain valid base32 characters"
+        // Fewer punctuation characters drop more, and ASCII-only labels are kept as is
+        let (_, label) = et.to_ascii_otpauth_uri("@").unwrap();
+        assert_eq!(label.issuer.as_deref(), Some("McCormick"));
+        assert_eq!(label.account_name, "Josez2fa@testemailcom");
+        let ascii = EasyTotp::from_base32_secret(
+            secret,
+            Some(String::from("McCormick")),
+            String::from("test@test-email.com"),
+        )
+        .unwrap();
+        let (uri, label) = ascii
+            .to_ascii_otpauth_uri(EasyTotp::ASCII_LABEL_PUNCTUATION)
+            .unwrap();
+        assert_eq!(uri, ascii.to_otpauth_uri().unwrap());
+        assert!(label.dropped.is_empty());
+
+        // An issuer with nothing left is left out, but the account name is required
+        let no_issuer = EasyTotp::from_base32_secret(
+            secret,
+            Some(String::from("株式会社")),
+            String::from("test@test-email.com"),
+        )
+        .unwrap();
+        let (uri, label) = no_issuer.to_ascii_otpauth_uri("@.-").unwrap();
+        assert_eq!(label.issuer, None);
+        assert!(uri.starts_with("otpauth://totp/test%40test-email.com?secret="));
+        let no_account =
+            EasyTotp::from_base32_secret(secret, None, String::from("株式会社")).unwrap();
+        assert!(matches!(
+            no_account.to_ascii_otpauth_uri(EasyTotp::ASCII_LABEL_PUNCTUATION),
+            Err(EasyTotpError::InvalidSetting(_))
+        ));
+
+        // Punctuation must itself be safe
+        for punctuation in [":", "é", "\n"] {
+            assert!(matches!(
+                ascii.to_ascii_otpauth_uri(punctuation),
+                Err(EasyTotpError::InvalidSetting(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_generate_token_with() {
+        let et = rfc_account();
+        let configured = et.generate_token_at(1_111_111_109).unwrap();
+
+        // RFC 6238 test vectors, independent of the configured SHA512, 6 digits, and 30 seconds
+        assert_eq!(
+            et.generate_token_with(Algorithm::SHA1, 8, 30, 59).unwrap(),
+            "94287082"
+        );
+        assert_eq!(
+            et.generate_token_with(Algorithm::SHA1, 8, 30, 1_111_111_109)
+                .unwrap(),
+            "07081804"
         );
 
-        // Delete file
-        fs::remove_file(filename).unwrap();
+        // Same tokens as a reconfigured copy, without touching the original or its cache
+        let variant = et
+            .clone()
+            .with_algorithm(Algorithm::SHA256)
+            .with_digits(7)
+            .unwrap()
+            .with_period(60)
+            .unwrap();
+        for unix_time in [0, 59, 1_700_000_000] {
+            assert_eq!(
+                et.generate_token_with(Algorithm::SHA256, 7, 60, unix_time)
+                    .unwrap(),
+                variant.generate_token_at(unix_time).unwrap()
+            );
+        }
+        assert_eq!(et.generate_token_at(1_111_111_109).unwrap(), configured);
+        assert_eq!(et.algorithm, Algorithm::SHA512);
+
+        // Steam Guard only works with its fixed format
+        assert_eq!(
+            et.generate_token_with(Algorithm::Steam, 5, 30, 59)
+                .unwrap()
+                .len(),
+            5
+        );
+        assert!(et.generate_token_with(Algorithm::Steam, 6, 30, 59).is_err());
+
+        assert!(et.generate_token_with(Algorithm::SHA1, 5, 30, 59).is_err());
+        assert!(et.generate_token_with(Algorithm::SHA1, 6, 0, 59).is_err());
     }
 
     #[test]
-    fn test_qr_terminal() {
-        let raw_secret = String::from("SUPERSecretSecretSecret");
-        let issuer = Some(String::from("McCormick"));
-        let account_name = String::from("Account_name");
+    fn test_qr_snapshot() {
+        use rand::{SeedableRng, rngs::StdRng};
 
-        let et = EasyTotp {
-            raw_secret,
-            issuer,
-            account_name,
-        };
+        // `StdRng` is only reproducible within a `rand` version, so upgrading `rand` may require updating the golden file.
+        // After checking that a rendering change is intended, regenerate it by writing `qr_snapshot()` to the file.
+        let et = EasyTotp::new_from_rng(
+            &mut StdRng::seed_from_u64(0x00ea_5170),
+            Some(String::from("McCormick")),
+            String::from("test@test-email.com"),
+        )
+        .unwrap();
+        let snapshot = et.qr_snapshot().unwrap();
+
+        assert_eq!(snapshot, include_str!("snapshots/qr_snapshot.txt"));
+        assert_eq!(snapshot, et.clone().qr_snapshot().unwrap());
+    }
 
-        match et.render_qr_terminal_full_direct() {
-            Ok(_) => println!("QR code rendered in terminal successfully."),
-            Err(e) => panic!("Error rendering QR code in terminal: {:?}", e),
+    #[test]
+    fn test_parse() {
+        let secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+        let uri = format!(
+            "otpauth://totp/McCormick:test%40test-email.com?secret={secret}&issuer=McCormick&digits=8"
+        );
+
+        let from_uri = EasyTotp::parse(&format!("  {uri}\n"), "fallback").unwrap();
+        assert_eq!(from_uri, EasyTotp::from_otpauth_uri(&uri).unwrap());
+        assert_eq!(from_uri.account_name, "test@test-email.com");
+        assert_eq!(from_uri.digits, 8);
+
+        let from_secret = EasyTotp::parse(secret, "fallback").unwrap();
+        assert_eq!(
+            from_secret,
+            EasyTotp::from_base32_secret(secret, None, String::from("fallback")).unwrap()
+        );
+        assert_eq!(from_secret.raw_secret, from_uri.raw_secret);
+
+        // URIs are never reinterpreted as secrets, so their own errors come through
+        assert!(matches!(
+            EasyTotp::parse(
+                "otpauth://hotp/McCormick:test?secret=GEZDGNBV&counter=0",
+                "fallback"
+            ),
+            Err(EasyTotpError::InvalidUri(_))
+        ));
+        assert!(matches!(
+            EasyTotp::parse("not a secret!", "fallback"),
+            Err(EasyTotpError::SecretParse(_))
+        ));
+        assert!(EasyTotp::parse("", "fallback").is_err());
+        assert!(EasyTotp::parse(secret, "test:test-email.com").is_err());
+    }
+
+    #[test]
+    fn test_clock_skew_tolerance_seconds() {
+        let et = rfc_account();
+        assert_eq!(et.clock_skew_tolerance_seconds(), 60);
+        assert_eq!(
+            et.clone()
+                .with_period(60)
+                .unwrap()
+                .with_skew(3)
+                .clock_skew_tolerance_seconds(),
+            240
+        );
+
+        // Offsets below `period * skew` always work, and offsets of the tolerance or more never do
+        let server_time = 1_000_000_000;
+        for skew in 0..=2 {
+            let et = et.clone().with_skew(skew);
+            let tolerance = et.clock_skew_tolerance_seconds();
+            let guaranteed = tolerance - et.period;
+            for now in server_time..server_time + et.period {
+                for offset in [0, guaranteed / 2, guaranteed] {
+                    let token = et.generate_token_at(now + offset).unwrap();
+                    assert!(et.verify_token_at(&token, now).unwrap());
+                    let token = et.generate_token_at(now - offset).unwrap();
+                    assert!(et.verify_token_at(&token, now).unwrap());
+                }
+                let token = et.generate_token_at(now + tolerance).unwrap();
+                assert!(!et.verify_token_at(&token, now).unwrap());
+                let token = et.generate_token_at(now - tolerance).unwrap();
+                assert!(!et.verify_token_at(&token, now).unwrap());
+            }
         }
     }
 
     #[test]
-    fn test_code_generation() {
-        let raw_secret = String::from("SUPERSecretSecretSecret");
+    fn test_new_checked() {
         let issuer = Some(String::from("McCormick"));
         let account_name = String::from("test@test-email.com");
+
+        let et = EasyTotp::new_checked(issuer.clone(), account_name.clone()).unwrap();
+        assert_eq!(et.raw_secret.len(), 20);
+        assert_eq!(et.issuer, issuer);
+
+        // Every byte value survives, including ones that aren't valid UTF-8
         let et = EasyTotp {
-            raw_secret: raw_secret.clone(),
+            raw_secret: Zeroizing::new((0..=255).collect()),
             issuer: issuer.clone(),
             account_name: account_name.clone(),
+            ..Default::default()
         };
+        et.check_secret_round_trip().unwrap();
+
+        assert!(matches!(
+            EasyTotp::new_checked(Some(String::from("Mc:Cormick")), account_name),
+            Err(EasyTotpError::InvalidSetting(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_qr_terminal() {
+        let et = rfc_account();
+
+        for size in [QRSize::Full, QRSize::Mini] {
+            for mode in [QRColorMode::Direct, QRColorMode::Inverted] {
+                let mut output = Vec::new();
+                et.write_qr_terminal(&mut output, mode, size).unwrap();
+
+                let mut expected = et.qr_text(size, mode).unwrap().join("\n");
+                expected.push('\n');
+                assert_eq!(String::from_utf8(output).unwrap(), expected);
+            }
+        }
+
+        // Write errors are reported rather than panicking
+        let mut full = [0_u8; 16];
+        assert!(
+            et.write_qr_terminal(&mut &mut full[..], QRColorMode::Direct, QRSize::Full)
+                .is_err()
+        );
+    }
 
-        let token1 = et.generate_token().unwrap();
-        let token2 = et.generate_token().unwrap();
+    #[test]
+    fn test_regenerate_secret() {
+        let issuer = Some(String::from("McCormick"));
+        let account_name = String::from("test@test-email.com");
+        let mut et = EasyTotp::new_with_secret_length(issuer.clone(), account_name.clone(), 32)
+            .unwrap()
+            .with_digits(8)
+            .unwrap();
 
-        assert_eq!(token1, token2);
+        // Fill the cache, which must not outlive the old secret
+        let old_token = et.generate_token_at(1_700_000_000).unwrap();
+        let old_uri = et.to_otpauth_uri().unwrap();
+        let old_secret = et.raw_secret.clone();
 
-        thread::sleep(time::Duration::from_secs(30));
+        et.regenerate_secret().unwrap();
+        assert_ne!(et.raw_secret, old_secret);
+        assert_eq!(et.raw_secret.len(), 32);
+        assert_ne!(et.generate_token_at(1_700_000_000).unwrap(), old_token);
+        assert!(!et.verify_token_at(&old_token, 1_700_000_000).unwrap());
+        assert_ne!(et.to_otpauth_uri().unwrap(), old_uri);
 
-        let token3 = et.generate_token().unwrap();
-        assert_ne!(token1, token3);
+        // Everything else is kept
+        assert_eq!(
+            (et.issuer.clone(), et.account_name.clone()),
+            (issuer, account_name)
+        );
+        assert_eq!(et.digits, 8);
+    }
 
-        assert_eq!((6, 6, 6), (token1.len(), token2.len(), token3.len()));
+    #[test]
+    fn test_icon_url() {
+        let et = rfc_account();
+        let plain_uri = et.to_otpauth_uri().unwrap();
 
+        let et = et
+            .with_icon_url("https://example.com/icons/totp.png?size=64&v=2")
+            .unwrap();
+        let uri = et.to_otpauth_uri().unwrap();
         assert_eq!(
-            (true, true, true),
-            (
-                token1.parse::<u32>().is_ok(),
-                token2.parse::<u32>().is_ok(),
-                token3.parse::<u32>().is_ok(),
+            uri,
+            format!(
+                "{plain_uri}&image=https%3A%2F%2Fexample.com%2Ficons%2Ftotp.png%3Fsize%3D64%26v%3D2"
             )
         );
+
+        // The QR code carries the icon too, and it survives importing the URI again
+        let mut decoder =
+            rqrr::PreparedImage::prepare(et.create_qr_image_buffer().unwrap().to_luma8());
+        let (_, content) = decoder.detect_grids()[0].decode().unwrap();
+        assert_eq!(content, uri);
+        assert_eq!(EasyTotp::from_otpauth_uri(&uri).unwrap(), et);
+
+        // An invalid icon in an imported URI is ignored rather than failing the import
+        let imported =
+            EasyTotp::from_otpauth_uri(&format!("{plain_uri}&image=http%3A%2F%2Fexample.com"))
+                .unwrap();
+        assert_eq!(imported.icon_url, None);
+
+        for invalid in [
+            "http://example.com/icon.png",
+            "example.com/icon.png",
+            "https://",
+            "https:///icon.png",
+            "https://example.com/my icon.png",
+            "javascript:alert(1)",
+        ] {
+            assert!(
+                matches!(
+                    et.clone().with_icon_url(invalid),
+                    Err(EasyTotpError::InvalidSetting(_))
+                ),
+                "{invalid:?} should be rejected"
+            );
+        }
+
+        let built = EasyTotp::builder()
+            .account_name("test@test-email.com")
+            .icon_url("https://example.com/icon.png")
+            .build()
+            .unwrap();
+        assert_eq!(
+            built.icon_url.as_deref(),
+            Some("https://example.com/icon.png")
+        );
+        assert!(
+            EasyTotp::builder()
+                .account_name("test@test-email.com")
+                .icon_url("http://example.com/icon.png")
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_from_otpauth_uri_hardening() {
+        let base = "otpauth://totp/McCormick:test?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+        assert!(EasyTotp::from_otpauth_uri(base).is_ok());
+
+        for invalid in [
+            // Missing secret
+            String::from("otpauth://totp/McCormick:test?issuer=McCormick"),
+            String::from("otpauth://totp/McCormick:test"),
+            // Repeated parameters, even if one of them is empty
+            format!("{base}&secret=JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP"),
+            format!("{base}&digits=8&digits=6"),
+            String::from("otpauth://totp/test?secret=&secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"),
+            format!("{base}&x=1&x=2"),
+            // Percent-encoding that isn't UTF-8, in the label and in the query
+            String::from("otpauth://totp/McCormick:%FF%FE?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"),
+            String::from("otpauth://totp/%E4%B8?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"),
+            format!("{base}&issuer=%FF"),
+            // Too long to have come from a QR code
+            format!(
+                "otpauth://totp/{}?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ",
+                "a".repeat(100_000)
+            ),
+            format!("{base}&period=99999999999999999999"),
+            // Labels that URL parsing resolves away or leaves empty, which wouldn't round trip
+            String::from("otpauth://totp/.?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"),
+            String::from("otpauth://totp/..?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"),
+            String::from("otpauth://totp/%2E%2E?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"),
+            String::from("otpauth://totp/%2e?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"),
+            String::from(
+                "otpauth://totp/McCormick/../test?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ",
+            ),
+            String::from("otpauth://totp/?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"),
+            String::from("otpauth://totp/McCormick:..?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"),
+            String::from("otpauth://totp/.:test?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"),
+        ] {
+            assert!(
+                EasyTotp::from_otpauth_uri(&invalid).is_err(),
+                "{:?} should be rejected",
+                &invalid[..invalid.len().min(100)]
+            );
+        }
+
+        // Such names can't be exported either
+        for (issuer, account_name) in [(None, "."), (None, ".."), (Some(".."), "test")] {
+            let et = EasyTotp {
+                raw_secret: Zeroizing::new(vec![0; MIN_SECRET_LEN]),
+                issuer: issuer.map(String::from),
+                account_name: String::from(account_name),
+                ..Default::default()
+            };
+            assert!(matches!(
+                et.to_otpauth_uri(),
+                Err(EasyTotpError::InvalidSetting(_))
+            ));
+        }
+        // Dots within a name are fine
+        let et = EasyTotp::from_otpauth_uri(
+            "otpauth://totp/McCormick:...?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ",
+        )
+        .unwrap();
+        assert_eq!(et.account_name, "...");
+
+        // Unknown parameters are ignored, and long labels are fine as long as a QR code can hold them
+        let et = EasyTotp::from_otpauth_uri(&format!("{base}&foo=bar&&color=blue")).unwrap();
+        assert_eq!(et.account_name, "test");
+        let long = format!(
+            "otpauth://totp/{}?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ",
+            "a".repeat(2_000)
+        );
+        assert_eq!(
+            EasyTotp::from_otpauth_uri(&long)
+                .unwrap()
+                .account_name
+                .len(),
+            2_000
+        );
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_from_otpauth_uri_never_panics(input in proptest::prelude::any::<String>()) {
+            let _ = EasyTotp::from_otpauth_uri(&input);
+            let _ = EasyTotp::from_otpauth_uri(&format!("otpauth://totp/{input}"));
+            let _ = EasyTotp::from_otpauth_uri(&format!("otpauth://steam/{input}"));
+        }
+
+        #[test]
+        fn prop_from_otpauth_uri_random_query(
+            label in "[A-Za-z0-9:%@. ]{0,40}",
+            query in "(secret|issuer|digits|period|algorithm|image|x|=|&|%[0-9A-F]{0,2}|[A-Z2-7]{1,8}|[0-9]{1,3}){0,16}",
+        ) {
+            if let Ok(et) = EasyTotp::from_otpauth_uri(&format!("otpauth://totp/{label}?{query}")) {
+                // Anything accepted must be a usable credential
+                proptest::prop_assert!(et.raw_secret.len() >= MIN_SECRET_LEN);
+                proptest::prop_assert!((6..=8).contains(&et.digits));
+                proptest::prop_assert!(et.period > 0);
+                proptest::prop_assert!(et.generate_token_at(1_700_000_000).is_ok());
+            }
+        }
+
+        #[test]
+        fn prop_from_otpauth_uri_structured(
+            issuer in proptest::option::of("[A-Za-z0-9 ]{1,20}"),
+            account_name in "[A-Za-z0-9@. ]{1,30}",
+            secret in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..40),
+            algorithm in proptest::sample::select(vec!["SHA1", "SHA256", "SHA512", "MD5"]),
+            digits in 0_usize..12,
+            period in 0_u64..120,
+            unknown in proptest::option::of("x-[a-z]{1,8}"),
+            repeat in proptest::option::of(proptest::sample::select(vec!["secret", "digits", "period", "algorithm"])),
+        ) {
+            let secret_base32 = EasyTotp {
+                raw_secret: Zeroizing::new(secret.clone()),
+                ..Default::default()
+            }
+            .secret_base32();
+
+            let mut params = vec![
+                (String::from("secret"), secret_base32),
+                (String::from("algorithm"), String::from(algorithm)),
+                (String::from("digits"), digits.to_string()),
+                (String::from("period"), period.to_string()),
+            ];
+            let label = match &issuer {
+                Some(issuer) => {
+                    params.push((String::from("issuer"), issuer.clone()));
+                    format!("{}:{}", urlencoding::encode(issuer), urlencoding::encode(&account_name))
+                }
+                None => urlencoding::encode(&account_name).into_owned(),
+            };
+            if let Some(unknown) = unknown {
+                params.push((unknown, String::from("1")));
+            }
+            if let Some(repeat) = repeat {
+                let value = params.iter().find(|(key, _)| key == repeat).unwrap().1.clone();
+                params.push((String::from(repeat), value));
+            }
+            let query: Vec<String> = params
+                .iter()
+                .map(|(key, value)| format!("{key}={}", urlencoding::encode(value)))
+                .collect();
+            let uri = format!("otpauth://totp/{label}?{}", query.join("&"));
+
+            let valid = secret.len() >= MIN_SECRET_LEN
+                && account_name != "."
+                && account_name != ".."
+                && algorithm != "MD5"
+                && (6..=8).contains(&digits)
+                && period > 0
+                && repeat.is_none();
+
+            match EasyTotp::from_otpauth_uri(&uri) {
+                Ok(et) => {
+                    proptest::prop_assert!(valid, "accepted {uri}");
+                    proptest::prop_assert_eq!(et.raw_secret.as_slice(), secret.as_slice());
+                    proptest::prop_assert_eq!(&et.issuer, &issuer);
+                    proptest::prop_assert_eq!(&et.account_name, &account_name);
+                    proptest::prop_assert_eq!((et.digits, et.period), (digits, period));
+
+                    let round_tripped = EasyTotp::from_otpauth_uri(&et.to_otpauth_uri().unwrap()).unwrap();
+                    proptest::prop_assert_eq!(round_tripped, et);
+                }
+                Err(e) => proptest::prop_assert!(!valid, "rejected {uri}: {e}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_token_grouped() {
+        let et = rfc_account().with_algorithm(Algorithm::SHA1);
+
+        // RFC 6238 test vectors, truncated to 6 and 8 digits
+        assert_eq!(et.generate_token_at(59).unwrap(), "287082");
+        assert_eq!(et.generate_token_grouped_at(59, " ").unwrap(), "287 082");
+        assert_eq!(et.generate_token_grouped_at(59, "-").unwrap(), "287-082");
+
+        let et = et.with_digits(8).unwrap();
+        assert_eq!(et.generate_token_grouped_at(59, " ").unwrap(), "9428 7082");
+        assert_eq!(
+            et.generate_token_grouped_at(1_111_111_109, "\u{2009}")
+                .unwrap(),
+            "0708\u{2009}1804"
+        );
+
+        let et = et.with_digits(7).unwrap();
+        let token = et.generate_token_at(59).unwrap();
+        assert_eq!(
+            et.generate_token_grouped_at(59, " ").unwrap(),
+            format!("{} {}", &token[..3], &token[3..])
+        );
+
+        // Removing the separator gives back the token to verify
+        let grouped = et.generate_token_grouped(" ").unwrap();
+        assert!(et.verify_token(&grouped.replace(' ', "")).unwrap());
+        assert!(!et.verify_token(&grouped).unwrap());
+    }
+
+    #[test]
+    fn test_qr_density() {
+        let et = rfc_account();
+
+        let version = et.qr_version().unwrap();
+        assert_eq!(et.qr_matrix().unwrap().len(), qr_modules(version) as usize);
+        assert_eq!(
+            et.check_qr_density(EasyTotp::RECOMMENDED_MAX_QR_VERSION)
+                .unwrap(),
+            version
+        );
+        assert_eq!(et.check_qr_density(version).unwrap(), version);
+
+        // Higher error correction needs at least as high a version
+        let high = et.clone().with_qr_ecc(QREccLevel::High);
+        assert!(high.qr_version().unwrap() > version);
+
+        // A long label with high error correction is too dense
+        let long = EasyTotp {
+            account_name: "a-very-long-account-name".repeat(8),
+            ..high
+        };
+        let error = long
+            .check_qr_density(EasyTotp::RECOMMENDED_MAX_QR_VERSION)
+            .unwrap_err();
+        let EasyTotpError::QrTooDense {
+            version: long_version,
+            max_version,
+        } = error
+        else {
+            panic!("expected QrTooDense, got {error:?}");
+        };
+        assert!(long_version > EasyTotp::RECOMMENDED_MAX_QR_VERSION);
+        assert_eq!(max_version, EasyTotp::RECOMMENDED_MAX_QR_VERSION);
+        assert!(
+            error
+                .to_string()
+                .contains("shorten the issuer or account name")
+        );
+        assert!(error.source().is_none());
+    }
+
+    #[test]
+    fn test_rfc6238_test_vectors() {
+        // RFC 6238 Appendix B: each algorithm uses an ASCII seed as long as its output
+        let seeds = [
+            (Algorithm::SHA1, &b"12345678901234567890"[..]),
+            (Algorithm::SHA256, &b"12345678901234567890123456789012"[..]),
+            (
+                Algorithm::SHA512,
+                &b"1234567890123456789012345678901234567890123456789012345678901234"[..],
+            ),
+        ];
+        // Unix time, then the SHA1, SHA256, and SHA512 tokens
+        let vectors: [(u64, [&str; 3]); 6] = [
+            (59, ["94287082", "46119246", "90693936"]),
+            (1_111_111_109, ["07081804", "68084774", "25091201"]),
+            (1_111_111_111, ["14050471", "67062674", "99943326"]),
+            (1_234_567_890, ["89005924", "91819424", "93441116"]),
+            (2_000_000_000, ["69279037", "90698825", "38618901"]),
+            // The time step counter no longer fits in 32 bits
+            (20_000_000_000, ["65353130", "77737706", "47863826"]),
+        ];
+
+        for (index, (algorithm, seed)) in seeds.into_iter().enumerate() {
+            let et = EasyTotp::builder()
+                .issuer("McCormick")
+                .account_name("test@test-email.com")
+                .algorithm(algorithm)
+                .digits(8)
+                .secret_bytes(seed)
+                .build()
+                .unwrap();
+            // The same account, after encoding the secret to base32 and into an otpauth URI
+            let imported = EasyTotp::from_otpauth_uri(&et.to_otpauth_uri().unwrap()).unwrap();
+            let six_digits = et.clone().with_digits(6).unwrap();
+
+            for (unix_time, tokens) in vectors {
+                let expected = tokens[index];
+                assert_eq!(
+                    et.generate_token_at(unix_time).unwrap(),
+                    expected,
+                    "{algorithm:?} at {unix_time}"
+                );
+                assert_eq!(imported.generate_token_at(unix_time).unwrap(), expected);
+                assert!(et.verify_token_at(expected, unix_time).unwrap());
+
+                // Dynamic truncation takes the same 31-bit value modulo 10^digits, so 6 digits are the last 6 of 8
+                assert_eq!(
+                    six_digits.generate_token_at(unix_time).unwrap(),
+                    expected[2..]
+                );
+            }
+        }
     }
 }
@@ -0,0 +1,698 @@
+//! Google Authenticator `otpauth-migration://` URIs, for moving many accounts with a single QR code
+//!
+//! The payload is a base64-encoded protobuf `MigrationPayload` message:
+//!
+//! ```text
+//! message MigrationPayload {
+//!   enum Algorithm { ALGORITHM_UNSPECIFIED = 0; SHA1 = 1; SHA256 = 2; SHA512 = 3; MD5 = 4; }
+//!   enum DigitCount { DIGIT_COUNT_UNSPECIFIED = 0; SIX = 1; EIGHT = 2; }
+//!   enum OtpType { OTP_TYPE_UNSPECIFIED = 0; HOTP = 1; TOTP = 2; }
+//!   message OtpParameters {
+//!     bytes secret = 1;
+//!     string name = 2;
+//!     string issuer = 3;
+//!     Algorithm algorithm = 4;
+//!     DigitCount digits = 5;
+//!     OtpType type = 6;
+//!     int64 counter = 7;
+//!   }
+//!   repeated OtpParameters otp_parameters = 1;
+//!   int32 version = 2;
+//!   int32 batch_size = 3;
+//!   int32 batch_index = 4;
+//!   int32 batch_id = 5;
+//! }
+//! ```
+
+use crate::{EasyTotp, EasyTotpError, QREccLevel, QrOptions, render_qr_image};
+
+use base64::{Engine as _, engine::general_purpose};
+use image::codecs::png::PngEncoder;
+use std::error::Error;
+use totp_rs::Algorithm;
+use zeroize::Zeroizing;
+
+/// Protobuf wire type of varint fields
+const WIRE_VARINT: u8 = 0;
+/// Protobuf wire type of length-delimited fields
+const WIRE_LEN: u8 = 2;
+
+/// `OtpType` value for counter-based accounts
+const OTP_TYPE_HOTP: u64 = 1;
+/// `OtpType` value for time-based accounts
+const OTP_TYPE_TOTP: u64 = 2;
+
+/// Scheme and host every migration URI starts with
+const MIGRATION_PREFIX: &str = "otpauth-migration://offline?";
+
+/// Creates an `otpauth-migration://offline?data=...` URI that imports every account into Google Authenticator at once
+///
+/// Google Authenticator only supports 30-second periods and 6 or 8 digits, so other settings are rejected rather than silently changed.
+///
+/// BEWARE: URI contains the secrets of every account!!
+///
+/// ```rust
+/// use easy_totp::{Algorithm, EasyTotp, export_migration_uri};
+///
+/// let alice = EasyTotp::new(Some(String::from("McCormick")), String::from("alice@test-email.com")).unwrap();
+/// let bob = EasyTotp::new(Some(String::from("McCormick")), String::from("bob@test-email.com"))
+///     .unwrap()
+///     .with_algorithm(Algorithm::SHA1);
+///
+/// let uri = export_migration_uri(&[&alice, &bob]).unwrap();
+/// assert!(uri.starts_with("otpauth-migration://offline?data="));
+/// ```
+///
+/// ## Errors
+/// This function will return an error if an account uses a period other than 30 seconds, a digit count other than 6 or 8,
+/// or Steam Guard tokens.
+pub fn export_migration_uri(accounts: &[&EasyTotp]) -> Result<String, EasyTotpError> {
+    let mut payload = Vec::new();
+
+    for account in accounts {
+        let parameters = encode_otp_parameters(account)?;
+        write_len_field(&mut payload, 1, &parameters);
+    }
+    write_varint_field(&mut payload, 2, 1); // version
+    write_varint_field(&mut payload, 3, 1); // batch_size
+
+    Ok(format!(
+        "{MIGRATION_PREFIX}data={}",
+        urlencoding::encode(&general_purpose::STANDARD.encode(payload))
+    ))
+}
+
+/// Creates a new PNG with a QR code of the `otpauth-migration://` URI for every account
+///
+/// QR codes only hold a limited amount of data, so split very long account lists over several QR codes.
+///
+/// BEWARE: PNG image contains the secrets of every account!!
+///
+/// ## Errors
+/// This function will return an error if an account can't be exported, or the URI is too long to fit in a QR code.
+pub fn create_migration_qr_png(accounts: &[&EasyTotp]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let image = render_qr_image(
+        &export_migration_uri(accounts)?,
+        QrOptions {
+            ecc: QREccLevel::Low,
+            ..QrOptions::default()
+        },
+        8,
+    )?;
+
+    let mut buffer = Vec::new();
+    image.write_with_encoder(PngEncoder::new(&mut buffer))?;
+
+    Ok(buffer)
+}
+
+/// `DedupReport` summarizes what `dedup_accounts` removed
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct DedupReport {
+    /// How many accounts were kept
+    pub kept: usize,
+    /// How many accounts were removed as duplicates of a kept one
+    pub removed: usize,
+}
+
+/// Removes accounts that generate the same tokens as an earlier account, keeping the first one's label
+///
+/// Accounts are compared with `EasyTotp::same_credential`, so the same secret under different labels (as repeated
+/// Google Authenticator exports accumulate) counts as a duplicate, while the same secret with different digits or period does not.
+/// The order of the kept accounts is preserved.
+///
+/// ```rust
+/// use easy_totp::{EasyTotp, dedup_accounts};
+///
+/// let secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+/// let accounts = vec![
+///     EasyTotp::from_base32_secret(secret, Some(String::from("McCormick")), String::from("test@test-email.com")).unwrap(),
+///     EasyTotp::from_base32_secret(secret, None, String::from("McCormick (old export)")).unwrap(),
+/// ];
+///
+/// let (accounts, report) = dedup_accounts(accounts);
+/// assert_eq!(accounts.len(), 1);
+/// assert_eq!(report.removed, 1);
+/// ```
+#[must_use]
+pub fn dedup_accounts(accounts: Vec<EasyTotp>) -> (Vec<EasyTotp>, DedupReport) {
+    let mut kept: Vec<EasyTotp> = Vec::with_capacity(accounts.len());
+    let mut removed = 0;
+
+    for account in accounts {
+        if kept
+            .iter()
+            .any(|existing| existing.same_credential(&account))
+        {
+            removed += 1;
+        } else {
+            kept.push(account);
+        }
+    }
+
+    let report = DedupReport {
+        kept: kept.len(),
+        removed,
+    };
+    (kept, report)
+}
+
+/// Reads every TOTP account from an `otpauth-migration://offline?data=...` URI like `import_migration_uri`, without duplicates
+///
+/// See `dedup_accounts`. To deduplicate across the several URIs of a batched export, import each with `import_migration_uri`
+/// and pass them all to `dedup_accounts` at once.
+///
+/// BEWARE: URI contains the secrets of every account!!
+///
+/// ## Errors
+/// This function will return an error in the same cases as `import_migration_uri`.
+pub fn import_migration_uri_dedup(
+    uri: &str,
+) -> Result<(Vec<EasyTotp>, DedupReport), EasyTotpError> {
+    Ok(dedup_accounts(import_migration_uri(uri)?))
+}
+
+/// Reads every TOTP account from an `otpauth-migration://offline?data=...` URI, as exported by Google Authenticator
+///
+/// One URI may hold many accounts. Counter-based (HOTP) accounts are skipped. Names of the form `Issuer:account` are split,
+/// with the issuer only taken from the name when the payload has no separate issuer.
+///
+/// BEWARE: URI contains the secrets of every account!!
+///
+/// ```rust
+/// use easy_totp::{EasyTotp, export_migration_uri, import_migration_uri};
+///
+/// let alice = EasyTotp::new(Some(String::from("McCormick")), String::from("alice@test-email.com")).unwrap();
+/// let uri = export_migration_uri(&[&alice]).unwrap();
+///
+/// let accounts = import_migration_uri(&uri).unwrap();
+/// assert_eq!(accounts, vec![alice]);
+/// ```
+///
+/// ## Errors
+/// This function will return an error if the URI or its base64 data is malformed, the payload contains unknown or malformed protobuf fields,
+/// or an account uses unsupported settings (e.g. MD5) or a secret shorter than 128 bits.
+pub fn import_migration_uri(uri: &str) -> Result<Vec<EasyTotp>, EasyTotpError> {
+    let Some(query) = uri.strip_prefix(MIGRATION_PREFIX) else {
+        return Err(EasyTotpError::InvalidUri(format!(
+            "URI must start with {MIGRATION_PREFIX}"
+        )));
+    };
+
+    let Some(data) = query
+        .split('&')
+        .find_map(|param| param.strip_prefix("data="))
+    else {
+        return Err(EasyTotpError::InvalidUri(String::from(
+            "migration URI has no data parameter",
+        )));
+    };
+
+    let Ok(data) = urlencoding::decode(data) else {
+        return Err(EasyTotpError::InvalidUri(String::from(
+            "migration data is not valid percent-encoded UTF-8",
+        )));
+    };
+
+    // Padding is optional in the wild, so accept data with and without it
+    let payload = match general_purpose::STANDARD_NO_PAD.decode(data.trim_end_matches('=')) {
+        Ok(payload) => Zeroizing::new(payload),
+        Err(e) => {
+            return Err(EasyTotpError::InvalidUri(format!(
+                "migration data is not valid base64: {e}"
+            )));
+        }
+    };
+
+    let mut accounts = Vec::new();
+    let mut reader = ProtoReader { bytes: &payload };
+    while let Some((field, value)) = reader.next_field()? {
+        match (field, value) {
+            (1, ProtoValue::Len(parameters)) => {
+                if let Some(account) = decode_otp_parameters(parameters)? {
+                    accounts.push(account);
+                }
+            }
+            // version, batch_size, batch_index, and batch_id
+            (2..=5, ProtoValue::Varint(_)) => {}
+            (field, _) => return Err(unexpected_field("MigrationPayload", field)),
+        }
+    }
+
+    Ok(accounts)
+}
+
+/// Decodes one `OtpParameters` message, or `None` for an HOTP account
+fn decode_otp_parameters(message: &[u8]) -> Result<Option<EasyTotp>, EasyTotpError> {
+    let mut secret = Zeroizing::new(Vec::new());
+    let mut name = String::new();
+    let mut issuer = String::new();
+    let mut algorithm = Algorithm::SHA1;
+    let mut digits = 6;
+    let mut otp_type = OTP_TYPE_TOTP;
+
+    let mut reader = ProtoReader { bytes: message };
+    while let Some((field, value)) = reader.next_field()? {
+        match (field, value) {
+            (1, ProtoValue::Len(bytes)) => secret = Zeroizing::new(bytes.to_vec()),
+            (2, ProtoValue::Len(bytes)) => name = proto_string(bytes)?,
+            (3, ProtoValue::Len(bytes)) => issuer = proto_string(bytes)?,
+            (4, ProtoValue::Varint(number)) => {
+                algorithm = match number {
+                    0 | 1 => Algorithm::SHA1,
+                    2 => Algorithm::SHA256,
+                    3 => Algorithm::SHA512,
+                    number => {
+                        return Err(EasyTotpError::InvalidSetting(format!(
+                            "unsupported migration algorithm {number}"
+                        )));
+                    }
+                };
+            }
+            (5, ProtoValue::Varint(number)) => {
+                digits = match number {
+                    0 | 1 => 6,
+                    2 => 8,
+                    number => {
+                        return Err(EasyTotpError::InvalidSetting(format!(
+                            "unsupported migration digit count {number}"
+                        )));
+                    }
+                };
+            }
+            (6, ProtoValue::Varint(number)) => otp_type = number,
+            // The HOTP counter
+            (7, ProtoValue::Varint(_)) => {}
+            (field, _) => return Err(unexpected_field("OtpParameters", field)),
+        }
+    }
+
+    match otp_type {
+        OTP_TYPE_HOTP => return Ok(None),
+        0 | OTP_TYPE_TOTP => {}
+        otp_type => {
+            return Err(EasyTotpError::InvalidSetting(format!(
+                "unsupported migration OTP type {otp_type}"
+            )));
+        }
+    }
+
+    let account_name = match name.split_once(':') {
+        Some((prefix, account_name)) => {
+            if issuer.is_empty() {
+                issuer = prefix.to_string();
+            }
+            account_name.trim_start().to_string()
+        }
+        None => name,
+    };
+
+    let account = EasyTotp {
+        raw_secret: secret,
+        issuer: Some(issuer).filter(|issuer| !issuer.is_empty()),
+        account_name,
+        algorithm,
+        ..Default::default()
+    }
+    .with_digits(digits)?;
+
+    // Catch unusable secrets now rather than on first use
+    account.totp()?;
+
+    Ok(Some(account))
+}
+
+/// Decodes a protobuf `string` field
+fn proto_string(bytes: &[u8]) -> Result<String, EasyTotpError> {
+    match String::from_utf8(bytes.to_vec()) {
+        Ok(string) => Ok(string),
+        Err(_) => Err(EasyTotpError::InvalidUri(String::from(
+            "migration payload contains a string that is not valid UTF-8",
+        ))),
+    }
+}
+
+/// The error for a field number or wire type that the schema doesn't define
+fn unexpected_field(message: &str, field: u64) -> EasyTotpError {
+    EasyTotpError::InvalidUri(format!(
+        "migration payload contains unknown or malformed {message} field {field}"
+    ))
+}
+
+/// A decoded protobuf field value
+enum ProtoValue<'a> {
+    Varint(u64),
+    Len(&'a [u8]),
+}
+
+/// Reads protobuf fields one at a time; only the varint and length-delimited wire types are supported
+struct ProtoReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ProtoReader<'a> {
+    /// Reads the next field number and value, or `None` at the end of the message
+    fn next_field(&mut self) -> Result<Option<(u64, ProtoValue<'a>)>, EasyTotpError> {
+        if self.bytes.is_empty() {
+            return Ok(None);
+        }
+
+        let key = self.read_varint()?;
+        let field = key >> 3;
+        let value = match key & 0x07 {
+            0 => ProtoValue::Varint(self.read_varint()?),
+            2 => {
+                let len = self.read_varint()?;
+                let Some(len) = usize::try_from(len)
+                    .ok()
+                    .filter(|&len| len <= self.bytes.len())
+                else {
+                    return Err(truncated());
+                };
+                let (value, rest) = self.bytes.split_at(len);
+                self.bytes = rest;
+                ProtoValue::Len(value)
+            }
+            _ => return Err(unexpected_field("protobuf", field)),
+        };
+
+        Ok(Some((field, value)))
+    }
+
+    /// Reads a base-128 varint
+    fn read_varint(&mut self) -> Result<u64, EasyTotpError> {
+        let mut value = 0u64;
+
+        for shift in (0..64).step_by(7) {
+            let Some((&byte, rest)) = self.bytes.split_first() else {
+                return Err(truncated());
+            };
+            self.bytes = rest;
+
+            value |= u64::from(byte & 0x7F) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(EasyTotpError::InvalidUri(String::from(
+            "migration payload contains a varint longer than 64 bits",
+        )))
+    }
+}
+
+/// The error for a payload that ends in the middle of a field
+fn truncated() -> EasyTotpError {
+    EasyTotpError::InvalidUri(String::from("migration payload is truncated"))
+}
+
+/// Encodes one account as an `OtpParameters` message
+fn encode_otp_parameters(account: &EasyTotp) -> Result<Vec<u8>, EasyTotpError> {
+    if account.algorithm == Algorithm::Steam {
+        return Err(EasyTotpError::InvalidSetting(String::from(
+            "Google Authenticator migration does not support Steam Guard accounts",
+        )));
+    }
+
+    if account.period != 30 {
+        return Err(EasyTotpError::InvalidSetting(format!(
+            "Google Authenticator migration only supports 30 second periods, not {}",
+            account.period
+        )));
+    }
+
+    let digits = match account.digits {
+        6 => 1,
+        8 => 2,
+        digits => {
+            return Err(EasyTotpError::InvalidSetting(format!(
+                "Google Authenticator migration only supports 6 or 8 digits, not {digits}"
+            )));
+        }
+    };
+
+    let mut message = Vec::new();
+    write_len_field(&mut message, 1, &account.raw_secret);
+    write_len_field(&mut message, 2, account.account_name.as_bytes());
+    if let Some(issuer) = &account.issuer {
+        write_len_field(&mut message, 3, issuer.as_bytes());
+    }
+    write_varint_field(
+        &mut message,
+        4,
+        u64::from(algorithm_number(account.algorithm)),
+    );
+    write_varint_field(&mut message, 5, digits);
+    write_varint_field(&mut message, 6, OTP_TYPE_TOTP);
+
+    Ok(message)
+}
+
+/// The `Algorithm` enum value of the migration payload; Steam Guard counts as SHA1, which it uses internally
+pub(crate) fn algorithm_number(algorithm: Algorithm) -> u8 {
+    if algorithm == Algorithm::SHA256 {
+        2
+    } else if algorithm == Algorithm::SHA512 {
+        3
+    } else {
+        1
+    }
+}
+
+/// Appends a base-128 varint
+fn write_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        // Truncation is intended: only the low 7 bits are kept
+        #[allow(clippy::cast_possible_truncation)]
+        buffer.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    buffer.push(value as u8);
+}
+
+/// Appends a varint field
+fn write_varint_field(buffer: &mut Vec<u8>, field: u8, value: u64) {
+    write_varint(buffer, u64::from(field << 3 | WIRE_VARINT));
+    write_varint(buffer, value);
+}
+
+/// Appends a length-delimited field
+fn write_len_field(buffer: &mut Vec<u8>, field: u8, bytes: &[u8]) {
+    write_varint(buffer, u64::from(field << 3 | WIRE_LEN));
+    write_varint(buffer, bytes.len() as u64);
+    buffer.extend_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account() -> EasyTotp {
+        EasyTotp {
+            raw_secret: Zeroizing::new(b"12345678901234567890".to_vec()),
+            issuer: Some(String::from("McCormick")),
+            account_name: String::from("test@test-email.com"),
+            algorithm: Algorithm::SHA1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_export_migration_payload() {
+        let uri = export_migration_uri(&[&account()]).unwrap();
+        let data = uri
+            .strip_prefix("otpauth-migration://offline?data=")
+            .unwrap();
+        let payload = general_purpose::STANDARD
+            .decode(urlencoding::decode(data).unwrap().as_bytes())
+            .unwrap();
+
+        // Encoded by hand from the schema in the module docs
+        let mut expected = vec![0x0A, 60, 0x0A, 20];
+        expected.extend_from_slice(b"12345678901234567890");
+        expected.extend_from_slice(&[0x12, 19]);
+        expected.extend_from_slice(b"test@test-email.com");
+        expected.extend_from_slice(&[0x1A, 9]);
+        expected.extend_from_slice(b"McCormick");
+        expected.extend_from_slice(&[0x20, 1, 0x28, 1, 0x30, 2]);
+        expected.extend_from_slice(&[0x10, 1, 0x18, 1]);
+        assert_eq!(payload, expected);
+
+        // Base64 padding and `+`/`/` must be percent-encoded
+        assert!(!data.contains(['+', '/', '=']));
+    }
+
+    #[test]
+    fn test_export_migration_rejects_unsupported_settings() {
+        let seven_digits = account().with_digits(7).unwrap();
+        let sixty_seconds = account().with_period(60).unwrap();
+        let steam = account().with_algorithm(Algorithm::Steam);
+
+        for unsupported in [seven_digits, sixty_seconds, steam] {
+            assert!(matches!(
+                export_migration_uri(&[&account(), &unsupported]),
+                Err(EasyTotpError::InvalidSetting(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_varint() {
+        for (value, bytes) in [
+            (0, vec![0x00]),
+            (1, vec![0x01]),
+            (127, vec![0x7F]),
+            (128, vec![0x80, 0x01]),
+            (300, vec![0xAC, 0x02]),
+        ] {
+            let mut buffer = Vec::new();
+            write_varint(&mut buffer, value);
+            assert_eq!(buffer, bytes);
+        }
+    }
+
+    #[test]
+    fn test_migration_qr_png() {
+        let accounts = [account(), account().with_algorithm(Algorithm::SHA512)];
+        let png = create_migration_qr_png(&[&accounts[0], &accounts[1]]).unwrap();
+
+        let img = image::load_from_memory(&png).unwrap().to_luma8();
+        let mut img = rqrr::PreparedImage::prepare(img);
+        let grids = img.detect_grids();
+        assert_eq!(grids.len(), 1);
+        let (_, content) = grids[0].decode().unwrap();
+        assert_eq!(
+            content,
+            export_migration_uri(&[&accounts[0], &accounts[1]]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_import_migration_round_trip() {
+        let accounts = [
+            account(),
+            account().with_algorithm(Algorithm::SHA512),
+            EasyTotp {
+                issuer: None,
+                ..account().with_digits(8).unwrap()
+            },
+        ];
+        let uri = export_migration_uri(&[&accounts[0], &accounts[1], &accounts[2]]).unwrap();
+
+        assert_eq!(import_migration_uri(&uri).unwrap(), accounts);
+    }
+
+    #[test]
+    fn test_import_migration_payloads() {
+        fn uri(payload: &[u8]) -> String {
+            format!(
+                "{MIGRATION_PREFIX}data={}",
+                urlencoding::encode(&general_purpose::STANDARD.encode(payload))
+            )
+        }
+
+        let totp = encode_otp_parameters(&account()).unwrap();
+
+        // An HOTP account with an `Issuer:account` name, which is skipped
+        let mut hotp = Vec::new();
+        write_len_field(&mut hotp, 1, b"12345678901234567890");
+        write_len_field(&mut hotp, 2, b"McCormick:hotp@test-email.com");
+        write_varint_field(&mut hotp, 6, OTP_TYPE_HOTP);
+        write_varint_field(&mut hotp, 7, 42);
+
+        // An account whose issuer is only part of its name
+        let mut named = Vec::new();
+        write_len_field(&mut named, 1, b"12345678901234567890");
+        write_len_field(&mut named, 2, b"McCormick: test@test-email.com");
+        write_varint_field(&mut named, 6, OTP_TYPE_TOTP);
+
+        let mut payload = Vec::new();
+        for parameters in [&totp, &hotp, &named] {
+            write_len_field(&mut payload, 1, parameters);
+        }
+        write_varint_field(&mut payload, 2, 1);
+
+        let imported = import_migration_uri(&uri(&payload)).unwrap();
+        assert_eq!(imported, vec![account(), account()]);
+
+        // Padding is optional
+        let unpadded = uri(&payload).replace("%3D", "");
+        assert_eq!(import_migration_uri(&unpadded).unwrap(), imported);
+
+        // Unknown fields, truncated payloads, and bad secrets are all errors rather than panics
+        let mut unknown = payload.clone();
+        write_varint_field(&mut unknown, 9, 1);
+        let mut too_short = Vec::new();
+        write_len_field(&mut too_short, 1, &{
+            let mut parameters = Vec::new();
+            write_len_field(&mut parameters, 1, b"short");
+            parameters
+        });
+        let mut md5 = totp.clone();
+        write_varint_field(&mut md5, 4, 4);
+        let mut md5_payload = Vec::new();
+        write_len_field(&mut md5_payload, 1, &md5);
+
+        for malformed in [
+            uri(&unknown),
+            uri(&payload[..payload.len() - 3]),
+            uri(&[0x0A, 0xFF]),
+            uri(&[0xFF; 11]),
+            uri(&too_short),
+            uri(&md5_payload),
+            format!("{MIGRATION_PREFIX}data=not*base64"),
+            format!("{MIGRATION_PREFIX}foo=bar"),
+            String::from("otpauth://totp/McCormick:test?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"),
+        ] {
+            assert!(import_migration_uri(&malformed).is_err(), "{malformed}");
+        }
+    }
+
+    #[test]
+    fn test_dedup_accounts() {
+        let relabeled = EasyTotp {
+            issuer: None,
+            account_name: String::from("McCormick (old export)"),
+            ..account()
+        };
+        let other_format = account().with_digits(8).unwrap();
+        let other_secret = EasyTotp::from_base32_secret(
+            "JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP",
+            Some(String::from("McCormick")),
+            String::from("test@test-email.com"),
+        )
+        .unwrap();
+
+        let (accounts, report) = dedup_accounts(vec![
+            account(),
+            relabeled.clone(),
+            other_format.clone(),
+            account(),
+            other_secret.clone(),
+        ]);
+        assert_eq!(accounts, vec![account(), other_format, other_secret]);
+        assert_eq!(
+            report,
+            DedupReport {
+                kept: 3,
+                removed: 2
+            }
+        );
+
+        // The first label wins
+        let (accounts, _) = dedup_accounts(vec![relabeled.clone(), account()]);
+        assert_eq!(accounts, vec![relabeled.clone()]);
+
+        let uri = export_migration_uri(&[&account(), &relabeled]).unwrap();
+        let (accounts, report) = import_migration_uri_dedup(&uri).unwrap();
+        assert_eq!(accounts, vec![account()]);
+        assert_eq!(report.removed, 1);
+
+        assert_eq!(
+            dedup_accounts(Vec::new()),
+            (Vec::new(), DedupReport::default())
+        );
+    }
+}
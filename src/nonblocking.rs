@@ -0,0 +1,142 @@
+//! Async wrappers around the blocking APIs, for Tokio-based servers
+
+use crate::{EasyTotp, EasyTotpError};
+
+use std::io;
+use std::panic;
+use std::path::PathBuf;
+use tokio::task;
+
+impl EasyTotp {
+    /// Verifies a user-submitted TOTP token like `verify_token`, on Tokio's blocking thread pool
+    ///
+    /// Verification itself is cheap, but this keeps the async API free of surprising blocking calls.
+    ///
+    /// ```rust
+    /// use easy_totp::EasyTotp;
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let issuer = Some(String::from("McCormick"));
+    /// let account_name = String::from("test@test-email.com");
+    /// let et = EasyTotp::new(issuer, account_name).unwrap();
+    ///
+    /// let token = et.generate_token().unwrap();
+    /// assert!(et.verify_token_async(token).await.unwrap());
+    /// # });
+    /// ```
+    ///
+    /// ## Errors
+    /// This function will return an error if the TOTP instance cannot be created, the system time is unavailable,
+    /// or the runtime is shutting down.
+    pub async fn verify_token_async(&self, token: String) -> Result<bool, EasyTotpError> {
+        let et = self.clone();
+        run_blocking(move || et.verify_token_at(&token, et.now()?)).await
+    }
+
+    /// Encrypts and writes to a file like `save_to_file`, on Tokio's blocking thread pool
+    ///
+    /// Deriving the encryption key is deliberately slow, so this keeps it from stalling the async runtime.
+    ///
+    /// ## Errors
+    /// This function will return an error if `save_to_file` fails, or the runtime is shutting down.
+    pub async fn save_to_file_async(
+        &self,
+        path: PathBuf,
+        password: String,
+    ) -> Result<(), EasyTotpError> {
+        let et = self.clone();
+        run_blocking(move || et.save_to_file(&path, &password)).await
+    }
+
+    /// Reads and decrypts a file like `load_from_file`, on Tokio's blocking thread pool
+    ///
+    /// Deriving the encryption key is deliberately slow, so this keeps it from stalling the async runtime.
+    ///
+    /// ## Errors
+    /// This function will return an error if `load_from_file` fails, or the runtime is shutting down.
+    pub async fn load_from_file_async(
+        path: PathBuf,
+        password: String,
+    ) -> Result<Self, EasyTotpError> {
+        run_blocking(move || EasyTotp::load_from_file(&path, &password)).await
+    }
+}
+
+/// Runs `f` on the blocking thread pool, passing on its panics
+async fn run_blocking<T, F>(f: F) -> Result<T, EasyTotpError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, EasyTotpError> + Send + 'static,
+{
+    match task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(e) if e.is_panic() => panic::resume_unwind(e.into_panic()),
+        // The task was cancelled because the runtime is shutting down
+        Err(e) => Err(EasyTotpError::Io(io::Error::other(e))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use totp_rs::Algorithm;
+
+    fn account() -> EasyTotp {
+        EasyTotp::from_base32_secret(
+            "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ",
+            Some(String::from("McCormick")),
+            String::from("test@test-email.com"),
+        )
+        .unwrap()
+        .with_algorithm(Algorithm::SHA1)
+        .with_digits(8)
+        .unwrap()
+        .with_clock(|| 1_111_111_109)
+    }
+
+    #[tokio::test]
+    async fn test_verify_token_async() {
+        let et = account();
+
+        // RFC 6238 SHA1 vector
+        assert!(
+            et.verify_token_async(String::from("07081804"))
+                .await
+                .unwrap()
+        );
+        assert!(
+            !et.verify_token_async(String::from("00000000"))
+                .await
+                .unwrap()
+        );
+        assert!(
+            !et.verify_token_async(String::from("not a token"))
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_file_async() {
+        let et = account();
+        let path = std::env::temp_dir().join(format!(
+            "easy_totp_test_file_async_{}.bin",
+            std::process::id()
+        ));
+
+        et.save_to_file_async(path.clone(), String::from("hunter2"))
+            .await
+            .unwrap();
+        let loaded = EasyTotp::load_from_file_async(path.clone(), String::from("hunter2"))
+            .await
+            .unwrap();
+        assert_eq!(loaded, et);
+
+        assert!(matches!(
+            EasyTotp::load_from_file_async(path.clone(), String::from("hunter3")).await,
+            Err(EasyTotpError::Encryption(_))
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
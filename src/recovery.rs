@@ -0,0 +1,207 @@
+//! Single-use recovery codes, for users who lose their authenticator device
+
+use crate::EasyTotpError;
+
+use base64::{Engine as _, engine::general_purpose};
+use constant_time_eq::constant_time_eq;
+use rand::{TryRngCore, rngs::OsRng};
+use sha2::{Digest, Sha256};
+
+/// Number of dash-separated groups in each recovery code
+const RECOVERY_CODE_GROUPS: usize = 3;
+
+/// Length of the random salt stored with each recovery code hash
+const SALT_LEN: usize = 16;
+
+/// Generates `count` random single-use recovery codes, formatted like `a1b2-c3d4-e5f6`
+///
+/// Each code consists of three groups of `group_len` lowercase hex characters, drawn from `OsRng`.
+/// Every character adds 4 bits of entropy, so a `group_len` of 4 gives 48 bits per code.
+///
+/// BEWARE: output contains secrets!! Show the codes to the user once, then only store their hashes (see `hash_recovery_code`).
+///
+/// ```rust
+/// use easy_totp::generate_recovery_codes;
+///
+/// let codes = generate_recovery_codes(10, 4).unwrap();
+/// assert_eq!(codes.len(), 10);
+/// assert_eq!(codes[0].len(), "a1b2-c3d4-e5f6".len());
+/// ```
+///
+/// ## Errors
+/// This function will return an error if `group_len` is zero, or if the random number generator fails.
+pub fn generate_recovery_codes(
+    count: usize,
+    group_len: usize,
+) -> Result<Vec<String>, EasyTotpError> {
+    if group_len == 0 {
+        return Err(EasyTotpError::InvalidSetting(String::from(
+            "recovery code group length must be greater than zero",
+        )));
+    }
+
+    let nibbles = RECOVERY_CODE_GROUPS * group_len;
+    let mut random_bytes = vec![0u8; nibbles.div_ceil(2)];
+
+    (0..count)
+        .map(|_| {
+            if let Err(e) = OsRng.try_fill_bytes(&mut random_bytes) {
+                return Err(EasyTotpError::Rng(Box::new(e)));
+            }
+
+            let mut code = String::with_capacity(nibbles + RECOVERY_CODE_GROUPS - 1);
+            for i in 0..nibbles {
+                if i > 0 && i % group_len == 0 {
+                    code.push('-');
+                }
+                let nibble = (random_bytes[i / 2] >> (4 * (i % 2))) & 0x0F;
+                code.push(char::from_digit(u32::from(nibble), 16).unwrap_or('0'));
+            }
+
+            Ok(code)
+        })
+        .collect()
+}
+
+/// Hashes a recovery code for storage, as `salt$hash` with a random salt and SHA-256
+///
+/// Dashes, whitespace, and letter case are ignored, so users may type the code however they like.
+///
+/// BEWARE: SHA-256 is fast, so keep the stored hashes as private as the TOTP secrets themselves!!
+///
+/// ```rust
+/// use easy_totp::{generate_recovery_codes, hash_recovery_code};
+///
+/// let codes = generate_recovery_codes(10, 4).unwrap();
+/// let hashes: Vec<String> = codes.iter().map(|code| hash_recovery_code(code).unwrap()).collect();
+/// ```
+///
+/// ## Errors
+/// This function will return an error if the random number generator fails to generate the salt.
+pub fn hash_recovery_code(code: &str) -> Result<String, EasyTotpError> {
+    let mut salt = [0u8; SALT_LEN];
+    if let Err(e) = OsRng.try_fill_bytes(&mut salt) {
+        return Err(EasyTotpError::Rng(Box::new(e)));
+    }
+
+    Ok(format!(
+        "{}${}",
+        general_purpose::STANDARD_NO_PAD.encode(salt),
+        general_purpose::STANDARD_NO_PAD.encode(salted_digest(&salt, code))
+    ))
+}
+
+/// Verifies a user-submitted recovery code against hashes from `hash_recovery_code`
+///
+/// Returns the index of the matching hash, or `None`. Remove the matching hash from storage so the code can't be used again.
+/// Every stored hash is checked, and digests are compared in constant time, so the time taken does not reveal which one matched.
+/// Malformed stored hashes never match.
+///
+/// ```rust
+/// use easy_totp::{hash_recovery_code, verify_recovery_code};
+///
+/// let hashes = vec![
+///     hash_recovery_code("a1b2-c3d4-e5f6").unwrap(),
+///     hash_recovery_code("0123-4567-89ab").unwrap(),
+/// ];
+///
+/// assert_eq!(verify_recovery_code("0123-4567-89AB", &hashes), Some(1));
+/// assert_eq!(verify_recovery_code("ffff-ffff-ffff", &hashes), None);
+/// ```
+#[must_use]
+pub fn verify_recovery_code<S: AsRef<str>>(code: &str, stored_hashes: &[S]) -> Option<usize> {
+    let mut matched = None;
+
+    for (index, stored) in stored_hashes.iter().enumerate() {
+        let Some((salt, hash)) = stored.as_ref().split_once('$') else {
+            continue;
+        };
+        let (Ok(salt), Ok(hash)) = (
+            general_purpose::STANDARD_NO_PAD.decode(salt),
+            general_purpose::STANDARD_NO_PAD.decode(hash),
+        ) else {
+            continue;
+        };
+
+        // No early return, so that every stored hash costs the same
+        if constant_time_eq(&salted_digest(&salt, code), &hash) && matched.is_none() {
+            matched = Some(index);
+        }
+    }
+
+    matched
+}
+
+/// SHA-256 of the salt followed by the normalized code
+fn salted_digest(salt: &[u8], code: &str) -> [u8; 32] {
+    let normalized: String = code
+        .chars()
+        .filter(|c| *c != '-' && !c.is_whitespace())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    hasher.update(normalized.as_bytes());
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recovery_code_format() {
+        let codes = generate_recovery_codes(10, 4).unwrap();
+        assert_eq!(codes.len(), 10);
+
+        for code in &codes {
+            let groups: Vec<&str> = code.split('-').collect();
+            assert_eq!(groups.len(), 3);
+            assert!(groups.iter().all(|group| group.len() == 4));
+            assert!(groups.iter().all(|group| {
+                group
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+            }));
+        }
+
+        // Odd group lengths must not leave a trailing partial group
+        let code = &generate_recovery_codes(1, 5).unwrap()[0];
+        assert_eq!(code.len(), 17);
+
+        assert!(generate_recovery_codes(0, 4).unwrap().is_empty());
+        assert!(matches!(
+            generate_recovery_codes(1, 0),
+            Err(EasyTotpError::InvalidSetting(_))
+        ));
+    }
+
+    #[test]
+    fn test_recovery_code_hash_and_verify() {
+        let codes = generate_recovery_codes(5, 4).unwrap();
+        let hashes: Vec<String> = codes
+            .iter()
+            .map(|code| hash_recovery_code(code).unwrap())
+            .collect();
+
+        for (index, code) in codes.iter().enumerate() {
+            assert_eq!(verify_recovery_code(code, &hashes), Some(index));
+            // Users may drop the dashes or type in upper case
+            let sloppy = code.replace('-', "").to_uppercase();
+            assert_eq!(verify_recovery_code(&sloppy, &hashes), Some(index));
+        }
+
+        // Salts differ, so hashing the same code twice gives different output
+        assert_ne!(
+            hash_recovery_code(&codes[0]).unwrap(),
+            hash_recovery_code(&codes[0]).unwrap()
+        );
+
+        assert_eq!(verify_recovery_code("0000-0000-0000", &hashes), None);
+        assert_eq!(
+            verify_recovery_code(&codes[0], &["not a hash", "$", "%%%$%%%"]),
+            None
+        );
+    }
+}
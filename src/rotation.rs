@@ -0,0 +1,104 @@
+//! Verification against several credentials at once, e.g. while rotating secrets
+
+use crate::{EasyTotp, EasyTotpError};
+
+/// Verifies a user-submitted TOTP token against every credential, returning `Ok(true)` if any of them accepts it
+///
+/// For graceful secret rotation: keep verifying against the old credential as well as the new one until the user has
+/// enrolled the new one. Also covers accounts enrolled on several devices with different secrets.
+///
+/// Every credential is checked, even after a match, and each check is constant-time like `EasyTotp::verify_token`,
+/// so the time taken does not reveal which credential matched. Each credential uses its own clock (see `EasyTotp::with_clock`).
+///
+/// ```rust
+/// use easy_totp::{EasyTotp, verify_token_any};
+///
+/// let issuer = Some(String::from("McCormick"));
+/// let account_name = String::from("test@test-email.com");
+/// let old = EasyTotp::new(issuer.clone(), account_name.clone()).unwrap();
+/// let new = EasyTotp::new(issuer, account_name).unwrap();
+///
+/// let token = old.generate_token().unwrap();
+/// assert!(verify_token_any(&[&old, &new], &token).unwrap());
+/// assert!(!verify_token_any(&[&new], "not a token").unwrap());
+/// ```
+///
+/// ## Errors
+/// This function will return an error if a TOTP instance cannot be created or the system time is unavailable.
+pub fn verify_token_any(credentials: &[&EasyTotp], token: &str) -> Result<bool, EasyTotpError> {
+    let mut matched = false;
+    for et in credentials {
+        // `|=` rather than `||`, so that a match doesn't skip the remaining credentials
+        matched |= et.verify_token_at(token, et.now()?)?;
+    }
+
+    Ok(matched)
+}
+
+/// Verifies a user-submitted TOTP token against every credential at an arbitrary Unix timestamp (in seconds)
+///
+/// See `verify_token_any`; this ignores the credentials' clocks.
+///
+/// ## Errors
+/// This function will return an error if a TOTP instance cannot be created.
+pub fn verify_token_any_at(
+    credentials: &[&EasyTotp],
+    token: &str,
+    unix_time: u64,
+) -> Result<bool, EasyTotpError> {
+    let mut matched = false;
+    for et in credentials {
+        matched |= et.verify_token_at(token, unix_time)?;
+    }
+
+    Ok(matched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Algorithm;
+
+    fn credential(secret: &str) -> EasyTotp {
+        EasyTotp::from_base32_secret(
+            secret,
+            Some(String::from("McCormick")),
+            String::from("test@test-email.com"),
+        )
+        .unwrap()
+        .with_algorithm(Algorithm::SHA1)
+        .with_digits(8)
+        .unwrap()
+    }
+
+    #[test]
+    fn test_verify_token_any() {
+        let old = credential("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+        let new = credential("JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP");
+        let new_token = new.generate_token_at(59).unwrap();
+
+        // RFC 6238 test vector of the old secret
+        assert!(verify_token_any_at(&[&old, &new], "94287082", 59).unwrap());
+        assert!(verify_token_any_at(&[&new, &old], "94287082", 59).unwrap());
+        assert!(verify_token_any_at(&[&old, &new], &new_token, 59).unwrap());
+        assert!(!verify_token_any_at(&[&new], "94287082", 59).unwrap());
+        assert!(!verify_token_any_at(&[&old, &new], "94287082", 59 + 90).unwrap());
+        assert!(!verify_token_any_at(&[&old, &new], "not a token", 59).unwrap());
+        assert!(!verify_token_any_at(&[], "94287082", 59).unwrap());
+
+        // Each credential is checked against its own clock
+        let old = old.with_clock(|| 59);
+        let new = new.with_clock(|| 1_000_000);
+        assert!(verify_token_any(&[&old, &new], "94287082").unwrap());
+        assert!(!verify_token_any(&[&old, &new], &new_token).unwrap());
+    }
+
+    #[test]
+    fn test_verify_token_any_error() {
+        let mut broken = credential("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+        broken.account_name = String::from("test:test-email.com");
+        let valid = credential("JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP");
+
+        assert!(verify_token_any_at(&[&valid, &broken], "94287082", 59).is_err());
+    }
+}